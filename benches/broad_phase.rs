@@ -0,0 +1,82 @@
+// Compares the brick grid broad phase with scanning every brick, run with
+// cargo bench --no-default-features --bench broad_phase
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rustanoid::geometry::*;
+use rustanoid::random::Random;
+
+const FIELD_SIZE: f32 = 800.0;
+const BRICK_COLUMNS: usize = 80;
+const BRICK_ROWS: usize = 50;
+const BALLS: usize = 64;
+const ROUNDS: usize = 200;
+const CELL_SIZE: f32 = 40.0;
+
+fn generated_bricks(random: &mut Random) -> Vec<Rectangle> {
+    let width = FIELD_SIZE / BRICK_COLUMNS as f32;
+    let height = FIELD_SIZE / 2.0 / BRICK_ROWS as f32;
+    let mut bricks = Vec::new();
+    for row in 0..BRICK_ROWS {
+        for column in 0..BRICK_COLUMNS {
+            if random.next_f32() < 0.8 {
+                let bottom = FIELD_SIZE / 2.0 + row as f32 * height;
+                bricks.push(Rectangle::make_by_size(column as f32 * width, bottom, width, height));
+            }
+        }
+    }
+    bricks
+}
+
+fn generated_balls(random: &mut Random) -> Vec<(Circle, FVector2d)> {
+    (0..BALLS)
+        .map(|_| {
+            let center = FPoint::new(random.next_f32() * FIELD_SIZE, random.next_f32() * FIELD_SIZE);
+            let movement = FVector2d::new(random.next_f32() * 4.0 - 2.0, random.next_f32() * 4.0 - 2.0);
+            (Circle::new(center, 4.0), movement)
+        })
+        .collect()
+}
+
+// Both scans report contacts by ascending brick index, so they compare as is
+fn contact_times(contacts: Vec<(usize, SweptCollision)>) -> Vec<(usize, f32)> {
+    contacts.into_iter().map(|(index, hit)| (index, hit.time)).collect()
+}
+
+fn time_rounds(mut find_contacts: impl FnMut() -> Vec<Vec<(usize, f32)>>) -> (Duration, Vec<Vec<(usize, f32)>>) {
+    let start = Instant::now();
+    let mut contacts = Vec::new();
+    for _ in 0..ROUNDS {
+        contacts = black_box(find_contacts());
+    }
+    (start.elapsed(), contacts)
+}
+
+fn main() {
+    let mut random = Random::new(2025);
+    let bricks = generated_bricks(&mut random);
+    let balls = generated_balls(&mut random);
+    let field = Rectangle::make_by_coords(0.0, 0.0, FIELD_SIZE, FIELD_SIZE);
+    let grid = SpatialGrid::with_items(&field, CELL_SIZE, bricks.iter());
+
+    let (full_scan_time, full_scan_contacts) = time_rounds(|| {
+        balls.iter()
+            .map(|(circle, movement)| contact_times(swept_collisions(circle, movement, bricks.iter().enumerate())))
+            .collect()
+    });
+    let (grid_time, grid_contacts) = time_rounds(|| {
+        balls.iter()
+            .map(|(circle, movement)| {
+                let candidates = grid.query(&swept_circle_bounds(circle, movement));
+                contact_times(swept_collisions(circle, movement, candidates.into_iter().map(|index| (index, &bricks[index]))))
+            })
+            .collect()
+    });
+
+    assert_eq!(full_scan_contacts, grid_contacts, "broad phase changed the narrow phase results");
+    let queries = (ROUNDS * BALLS) as f64;
+    println!("{} bricks, {} balls, {} rounds, {} contacts per round", bricks.len(), BALLS, ROUNDS, grid_contacts.iter().flatten().count());
+    println!("full scan: {:>10.2?} ({:.0} ns per ball)", full_scan_time, full_scan_time.as_nanos() as f64 / queries);
+    println!("grid:      {:>10.2?} ({:.0} ns per ball)", grid_time, grid_time.as_nanos() as f64 / queries);
+    println!("speed-up:  {:.1}x", full_scan_time.as_secs_f64() / grid_time.as_secs_f64());
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use rustanoid::game_state::*;
+use rustanoid::headless::*;
+use rustanoid::level::*;
+use rustanoid::replay::*;
+
+// Same levels the game plays unless --levels points elsewhere
+const DEFAULT_LEVELS_DIR: &str = "data/levels";
+
+fn run_script(script_path: &str, step_sec: f64, levels: Vec<Level>) {
+    let script_text = std::fs::read_to_string(script_path)
+        .unwrap_or_else(|err| exit_with_error(&format!("Can't read script {}: {}", script_path, err)));
+    let script = InputScript::parse(&script_text)
+        .unwrap_or_else(|err| exit_with_error(&format!("Can't parse script {}: {}", script_path, err)));
+    let mut clock = FixedStepClock::new(step_sec);
+    let mut game = new_game(levels);
+    let ticks = run(&mut game, &mut clock, &script);
+    println!("Ticks: {}, time: {:.2}s", ticks, clock.now_sec);
+    println!("{:#?}", game);
+}
+
+fn verify_replay(replay_path: &str, levels: Vec<Level>) {
+    let replay_text = std::fs::read_to_string(replay_path)
+        .unwrap_or_else(|err| exit_with_error(&format!("Can't read replay {}: {}", replay_path, err)));
+    let replay = Replay::parse(&replay_text)
+        .unwrap_or_else(|err| exit_with_error(&format!("Can't parse replay {}: {}", replay_path, err)));
+    // Sessions are recorded from the title screen, see main.rs
+    let mut game = new_game(levels).with_title_screen();
+    match replay.verify(&mut game) {
+        ReplayVerification::Match { hash } => println!("Replay OK, {} frames, hash {:016x}", replay.frames.len(), hash),
+        ReplayVerification::Mismatch { expected, actual } => {
+            eprintln!("Replay diverged: expected hash {:016x}, got {:016x}", expected, actual);
+            std::process::exit(1);
+        },
+        ReplayVerification::NoHashRecorded { actual } => {
+            eprintln!("Replay has no final hash, got {:016x}", actual);
+            std::process::exit(1);
+        },
+    }
+}
+
+fn load_levels(levels_dir: &str) -> Vec<Level> {
+    load_levels_from_dir(Path::new(levels_dir))
+        .unwrap_or_else(|err| exit_with_error(&format!("Can't load levels: {}", err)))
+}
+
+fn new_game(levels: Vec<Level>) -> GameState {
+    GameState::with_levels(levels).unwrap_or_else(|| exit_with_error("No levels to play"))
+}
+
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(2);
+}
+
+fn exit_with_usage(program: &str) -> ! {
+    eprintln!("Usage: {} [--levels <dir>] <script> [step_sec]", program);
+    eprintln!("       {} [--levels <dir>] --verify-replay <replay>", program);
+    std::process::exit(2);
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().collect();
+    let levels_dir = match args.iter().position(|arg| arg == "--levels") {
+        Some(index) if index + 1 < args.len() => {
+            let levels_dir = args.remove(index + 1);
+            args.remove(index);
+            levels_dir
+        },
+        Some(_) => exit_with_usage(&args[0]),
+        None => DEFAULT_LEVELS_DIR.to_string(),
+    };
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("--verify-replay") if args.len() == 3 => verify_replay(&args[2], load_levels(&levels_dir)),
+        Some(script_path) if !script_path.starts_with("--") && args.len() <= 3 => {
+            let step_sec = match args.get(2).map(|step| step.parse::<f64>()) {
+                None => 1.0 / 100.0,
+                Some(Ok(step_sec)) if step_sec.is_finite() && step_sec > 0.0 => step_sec,
+                Some(_) => {
+                    eprintln!("step_sec must be a positive number of seconds, got {}", args[2]);
+                    exit_with_usage(&args[0]);
+                },
+            };
+            run_script(script_path, step_sec, load_levels(&levels_dir));
+        },
+        _ => exit_with_usage(&args[0]),
+    }
+}
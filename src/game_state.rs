@@ -1,43 +1,91 @@
-use std::{alloc::GlobalAlloc, collections::HashSet};
-
-use allegro::KeyCode;
-
 use crate::geometry::*;
+use crate::level::*;
+use crate::paddle_bounce::*;
+use crate::power_up::*;
+use crate::random::*;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ball {
     pub position: Circle,
-    pub movement_vector: FVector2d
+    pub movement_vector: FVector2d,
+    pub stuck_offset: Option<f32>,
+}
+
+impl Ball {
+    pub fn is_stuck(&self) -> bool {
+        self.stuck_offset.is_some()
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BrickVariety {
     Standard { color: i32 },
     Steel
 }
 
 impl BrickVariety {
-    fn standard(color: i32) -> BrickVariety {
+    pub fn standard(color: i32) -> BrickVariety {
         BrickVariety::Standard { color }
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Brick {
     pub position: Rectangle,
     pub variety: BrickVariety,
+    pub hit_points: i32,
+    pub max_hit_points: i32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BrickHit {
+    Deflected,
+    Damaged,
+    Destroyed,
 }
 
 impl Brick {
-    fn new(x1: f32, y1: f32, x2: f32, y2: f32, variety: BrickVariety) -> Brick {
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32, variety: BrickVariety) -> Brick {
         Brick {
             position: Rectangle::make_by_coords(x1, y1, x2, y2),
-            variety: variety,
+            variety,
+            hit_points: 1,
+            max_hit_points: 1,
+        }
+    }
+
+    pub fn with_hit_points(mut self, hit_points: i32) -> Brick {
+        self.hit_points = hit_points;
+        self.max_hit_points = hit_points;
+        self
+    }
+
+    pub fn damage_taken(&self) -> i32 {
+        self.max_hit_points - self.hit_points
+    }
+
+    pub fn hit(&mut self) -> BrickHit {
+        if !self.is_breakable() {
+            return BrickHit::Deflected;
+        }
+        self.hit_points -= 1;
+        if self.hit_points > 0 {
+            BrickHit::Damaged
+        } else {
+            BrickHit::Destroyed
         }
     }
+
+    pub fn is_breakable(&self) -> bool {
+        !matches!(self.variety, BrickVariety::Steel)
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Paddle {
     pub position: Rectangle,
     pub vector: FVector2d
@@ -57,237 +105,989 @@ impl Paddle {
         } else {
             new_position
         };
+        // A paddle held at a wall does not move, bounces must not see the requested speed
+        if time_delta > 0.0 {
+            self.vector = FVector2d::new((limited_new_position.left() - self.position.left()) / time_delta, 0.0);
+        }
         self.position.mutable_set(limited_new_position);
     }
-}
 
-#[derive(Debug)]
-pub struct BallCollision {
-    pub ball: Ball,
-    pub brick_index: usize,
-    pub collision: Collision
-}
-
-impl BallCollision {
-    pub fn new(ball: Ball, brick_index: usize, collision: Collision) -> BallCollision {
-        BallCollision {
-            ball,
-            brick_index,
-            collision,
-        }
+    fn set_width(&mut self, width: f32, left_limit: f32, right_limit: f32) {
+        let center = self.position.center();
+        let resized = Rectangle::make_by_coords(center.x - width / 2.0, self.position.bottom(), center.x + width / 2.0, self.position.top());
+        let limited_resized = if resized.left() < left_limit {
+            resized.with_left_at(left_limit)
+        } else if resized.right() > right_limit {
+            resized.with_right_at(right_limit)
+        } else {
+            resized
+        };
+        self.position.mutable_set(limited_resized);
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyboardState {
     pub move_left: bool,
     pub move_right: bool,
     pub fire: bool,
+    pub pause: bool,
+    // World x the paddle center follows, for pointer control
+    pub pointer_x: Option<f32>,
+    // Analog stick position in -1..1, the deadzone is already applied
+    pub analog_x: f32,
 }
 
 impl KeyboardState {
-    pub fn from_key_set(set: &HashSet<KeyCode>) -> KeyboardState {
+    pub fn none() -> KeyboardState {
         KeyboardState {
-            move_left: set.contains(&KeyCode::A),
-            move_right: set.contains(&KeyCode::D),
-            fire: set.contains(&KeyCode::Space),
+            move_left: false,
+            move_right: false,
+            fire: false,
+            pause: false,
+            pointer_x: None,
+            analog_x: 0.0,
         }
     }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TimeState {
+    Title,
     Stopped,
     Running { last_update_time_sec: f64 },
+    Paused,
+    LifeLost,
+    LevelComplete,
     GameOver,
+    Victory,
 }
 
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreRules {
+    pub standard_hit: i32,
+    pub standard_destroyed: i32,
+    pub steel_hit: i32,
+    pub max_combo_multiplier: i32,
+    pub level_clear_bonus: i32,
+}
+
+impl Default for ScoreRules {
+    fn default() -> ScoreRules {
+        ScoreRules {
+            standard_hit: 10,
+            standard_destroyed: 50,
+            steel_hit: 0,
+            max_combo_multiplier: 5,
+            level_clear_bonus: 1000,
+        }
+    }
+}
+
+impl ScoreRules {
+    pub fn points_for(&self, variety: &BrickVariety, hit: &BrickHit) -> i32 {
+        match (variety, hit) {
+            (BrickVariety::Steel, _) => self.steel_hit,
+            (BrickVariety::Standard { .. }, BrickHit::Destroyed) => self.standard_hit + self.standard_destroyed,
+            (BrickVariety::Standard { .. }, _) => self.standard_hit,
+        }
+    }
+
+    // Every consecutive brick hit without touching the paddle raises the multiplier by one
+    pub fn combo_multiplier(&self, combo_hits: i32) -> i32 {
+        i32::min(combo_hits + 1, self.max_combo_multiplier)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtraLifeRules {
+    pub thresholds: Vec<i32>,
+    // A step that is not positive awards nothing after the list
+    pub then_every: Option<i32>,
+    pub max_lives: i32,
+}
+
+impl Default for ExtraLifeRules {
+    fn default() -> ExtraLifeRules {
+        ExtraLifeRules {
+            thresholds: vec![2000, 5000],
+            then_every: Some(10000),
+            max_lives: 9,
+        }
+    }
+}
+
+impl ExtraLifeRules {
+    // Score needed for the extra life with the given zero-based number
+    pub fn threshold(&self, extra_life_number: usize) -> Option<i32> {
+        match (self.thresholds.get(extra_life_number), self.thresholds.last(), self.then_every) {
+            (Some(threshold), _, _) => Some(*threshold),
+            (None, last, Some(every)) if every > 0 => {
+                let repeats = (extra_life_number - self.thresholds.len() + 1) as i32;
+                Some(last.copied().unwrap_or(0) + repeats * every)
+            },
+            (None, ..) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeedRules {
+    pub serve_speed: f32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    pub speed_up_percent: f32,
+    // Zero turns the paddle hit speed-up off
+    pub paddle_hits_per_speed_up: u32,
+    pub speed_up_on_top_wall: bool,
+    // Smallest angle between the ball path and the horizontal after a bounce
+    pub min_vertical_angle: f32,
+}
+
+impl Default for SpeedRules {
+    fn default() -> SpeedRules {
+        SpeedRules {
+            serve_speed: 120.0,
+            min_speed: 80.0,
+            max_speed: 300.0,
+            speed_up_percent: 5.0,
+            paddle_hits_per_speed_up: 4,
+            speed_up_on_top_wall: false,
+            min_vertical_angle: std::f32::consts::PI / 12.0,
+        }
+    }
+}
+
+impl SpeedRules {
+    pub fn speed_up(&self, vector: &FVector2d) -> FVector2d {
+        self.limit(&vector.clone().mul_scalar(1.0 + self.speed_up_percent / 100.0))
+    }
+
+    // Clamps the speed and turns paths that are too close to horizontal away
+    // from it, keeping the horizontal and vertical directions
+    pub fn limit(&self, vector: &FVector2d) -> FVector2d {
+        let speed = vector.length();
+        if speed == 0.0 {
+            return vector.clone();
+        }
+        let limited_speed = speed.clamp(self.min_speed, self.max_speed);
+        let min_vertical = limited_speed * self.min_vertical_angle.sin();
+        let vertical = vector.y * limited_speed / speed;
+        if vertical.abs() >= min_vertical {
+            return vector.clone().mul_scalar(limited_speed / speed);
+        }
+        let vertical_sign = if vector.y < 0.0 { -1.0 } else { 1.0 };
+        let horizontal_sign = if vector.x < 0.0 { -1.0 } else { 1.0 };
+        FVector2d::new(
+            horizontal_sign * limited_speed * self.min_vertical_angle.cos(),
+            vertical_sign * min_vertical
+        )
+    }
+}
+
+enum Obstacle {
+    Paddle,
+    Brick(usize),
+    Wall,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameSnapshot {
+    pub balls: Vec<Ball>,
+    pub paddle_position: Rectangle,
+}
+
+// The derived code is wrapped by the impls below so deserializing checks the
+// level fields and rebuilds the brick grid
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(remote = "Self"))]
 pub struct GameState {
     pub balls: Vec<Ball>,
-    pub bricks: Vec<Brick>,
+    // Change through set_bricks and add_brick so brick_grid stays in step
+    bricks: Vec<Brick>,
+    // Derived from bricks, not saved
+    #[cfg_attr(feature = "serde", serde(skip))]
+    brick_grid: SpatialGrid,
     pub field: Rectangle,
     pub paddle: Paddle,
     pub time_state: TimeState,
     pub lives_left: i32,
+    pub score: i32,
+    pub combo_hits: i32,
+    pub score_rules: ScoreRules,
+    pub extra_life_rules: ExtraLifeRules,
+    pub extra_lives_awarded: usize,
+    pub speed_rules: SpeedRules,
+    pub paddle_hits: u32,
+    pub paddle_bounce: PaddleBounce,
+    pub levels: Vec<Level>,
+    pub current_level: usize,
+    pub capsules: Vec<Capsule>,
+    pub active_effects: Vec<ActiveEffect>,
+    pub projectiles: Vec<Projectile>,
+    pub laser_cooldown_left_sec: f32,
+    pub power_up_rules: PowerUpRules,
+    pub random: Random,
+    pub serve_aim_angle: f32,
+    pub fire_was_down: bool,
+    pub pause_was_down: bool,
+    // Paddle direction from the move keys or the stick, in -1..1
+    pub paddle_input_x: f32,
+    pub paddle_target_x: Option<f32>,
+    pub accumulator_sec: f64,
+    pub previous_frame: FrameSnapshot,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for GameState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameState::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GameState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<GameState, D::Error> {
+        use serde::de::Error;
+        let mut game = GameState::deserialize(deserializer)?;
+        if game.current_level >= game.levels.len() {
+            return Err(D::Error::custom(format!("current level {} is out of range for {} levels", game.current_level, game.levels.len())));
+        }
+        // The level complete screen shows the name of the next level
+        if game.time_state == TimeState::LevelComplete && game.current_level + 1 == game.levels.len() {
+            return Err(D::Error::custom("the last level can't be waiting for a next one"));
+        }
+        game.rebuild_brick_grid();
+        Ok(game)
+    }
 }
 
 impl GameState {
     const PADDLE_SPEED: f32 = 200.0;
+    const POINTER_PADDLE_MAX_SPEED: f32 = 1000.0;
+    const MAX_BOUNCES_PER_TICK: usize = 8;
+    const MAX_SEPARATION_PASSES: usize = 4;
+    const WALL_THICKNESS: f32 = 100.0;
+    pub const FIXED_STEP_SEC: f64 = 1.0 / 200.0;
+    pub const MAX_STEPS_PER_TICK: u32 = 25;
+    const STEP_EPSILON_SEC: f64 = 1e-9;
+    const PADDLE_WIDTH: f32 = 50.0;
+    const BRICK_GRID_CELL_SIZE: f32 = 40.0;
+    const RANDOM_SEED: u64 = 0x5EED_2025;
+    pub const MAX_SERVE_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+    const SERVE_AIM_SPEED: f32 = 1.5;
 
     pub fn make_initial() -> GameState {
-        GameState {
-            balls: GameState::initial_balls_state(),
-            bricks: GameState::initial_bricks_state(),
-            field: Rectangle::make_by_coords(10.0, 10.0, 410.0, 460.0),
-            paddle: GameState::initial_paddle_state(),
+        GameState::with_levels(builtin_levels()).expect("built-in levels are not empty")
+    }
+
+    // None when there is no level to start on
+    pub fn with_levels(levels: Vec<Level>) -> Option<GameState> {
+        let first_level = levels.first()?;
+        let speed_rules = first_level.speed_rules.clone();
+        let bricks = first_level.bricks.clone();
+        let brick_grid = SpatialGrid::with_items(&first_level.field, GameState::BRICK_GRID_CELL_SIZE, bricks.iter().map(|brick| &brick.position));
+        let field = first_level.field.clone();
+        let balls = GameState::initial_balls_state(speed_rules.serve_speed);
+        let paddle = GameState::initial_paddle_state();
+        Some(GameState {
+            previous_frame: FrameSnapshot {
+                balls: balls.clone(),
+                paddle_position: paddle.position.clone(),
+            },
+            balls,
+            bricks,
+            brick_grid,
+            field,
+            paddle,
             time_state: TimeState::Stopped,
             lives_left: 3,
-        }
+            score: 0,
+            combo_hits: 0,
+            score_rules: ScoreRules::default(),
+            extra_life_rules: ExtraLifeRules::default(),
+            extra_lives_awarded: 0,
+            speed_rules,
+            paddle_hits: 0,
+            paddle_bounce: PaddleBounce::default(),
+            levels,
+            current_level: 0,
+            capsules: Vec::new(),
+            active_effects: Vec::new(),
+            projectiles: Vec::new(),
+            laser_cooldown_left_sec: 0.0,
+            power_up_rules: PowerUpRules::default(),
+            random: Random::new(GameState::RANDOM_SEED),
+            serve_aim_angle: 0.0,
+            fire_was_down: false,
+            pause_was_down: false,
+            paddle_input_x: 0.0,
+            paddle_target_x: None,
+            accumulator_sec: 0.0,
+        })
     }
 
     fn initial_paddle_state() -> Paddle {
         Paddle {
-            position: Rectangle::make_by_coords(50.0, 30.0, 100.0, 40.0),
+            position: Rectangle::make_by_size(50.0, 30.0, GameState::PADDLE_WIDTH, 10.0),
             vector: FVector2d::new(0.0, 0.0),
         }
     }
 
-    fn initial_balls_state() -> Vec<Ball> {
+    fn initial_balls_state(serve_speed: f32) -> Vec<Ball> {
         vec![
             Ball {
-                position: Circle::new(FPoint::new(70.0, 44.0), 4.0),
-                movement_vector: FVector2d::new(-15.0, 120.0),
+                position: Circle::new(FPoint::new(75.0, 44.0), 4.0),
+                movement_vector: FVector2d::new(0.0, serve_speed),
+                stuck_offset: Some(0.0),
             }
         ]
     }
 
-    fn initial_bricks_state() -> Vec<Brick> {
-        vec!(
-            Brick::new(10.0, 400.0, 50.0, 420.0, BrickVariety::standard(1)),
-            Brick::new(50.0, 400.0, 90.0, 420.0, BrickVariety::standard(2)),
-            Brick::new(10.0, 420.0, 50.0, 440.0, BrickVariety::standard(3)),
-        )
-    }
-
     pub fn tick(&mut self, timestamp_sec: f64, keyboard_state: KeyboardState) {
-        self.paddle.set_vector(FVector2d::zero());
+        self.paddle_input_x = keyboard_state.analog_x.clamp(-1.0, 1.0);
         if keyboard_state.move_left {
-            self.paddle.set_vector(FVector2d::new(-GameState::PADDLE_SPEED, 0.0));
+            self.paddle_input_x = -1.0;
         }
         if keyboard_state.move_right {
-            self.paddle.set_vector(FVector2d::new(GameState::PADDLE_SPEED, 0.0));
+            self.paddle_input_x = 1.0;
         }
-        if keyboard_state.fire && self.time_state == TimeState::Stopped {
+        self.paddle_target_x = keyboard_state.pointer_x;
+        let fire_pressed = keyboard_state.fire && !self.fire_was_down;
+        self.fire_was_down = keyboard_state.fire;
+        let pause_pressed = keyboard_state.pause && !self.pause_was_down;
+        self.pause_was_down = keyboard_state.pause;
+        if self.time_state == TimeState::Stopped {
             self.time_state = TimeState::Running { last_update_time_sec: timestamp_sec }
         }
-
         match self.time_state {
-            TimeState::Running { last_update_time_sec } => {
-                self.execute_movement(timestamp_sec, last_update_time_sec);
+            TimeState::Title | TimeState::LifeLost if fire_pressed => self.time_state = TimeState::Stopped,
+            TimeState::LevelComplete if fire_pressed => self.start_next_level(),
+            TimeState::GameOver if fire_pressed => self.restart(),
+            TimeState::Victory if fire_pressed => {
+                self.restart();
+                self.time_state = TimeState::Title;
             },
-            TimeState::Stopped | TimeState::GameOver => (),
+            TimeState::Running{..} if pause_pressed => self.time_state = TimeState::Paused,
+            // Resuming goes through Stopped so the paused time is never simulated
+            TimeState::Paused if pause_pressed => self.time_state = TimeState::Stopped,
+            TimeState::Running{..} if fire_pressed => self.release_stuck_balls(),
+            _ => (),
         }
+
+        if let TimeState::Running { last_update_time_sec } = self.time_state {
+            self.execute_movement(timestamp_sec, last_update_time_sec);
+        }
+    }
+
+    pub fn with_title_screen(mut self) -> GameState {
+        self.time_state = TimeState::Title;
+        self
+    }
+
+    // A new game on the same levels and rules, the held keys carry over so
+    // the fire press that restarted does not also serve
+    fn restart(&mut self) {
+        let mut fresh = GameState::with_levels(std::mem::take(&mut self.levels))
+            .expect("a game always has its levels");
+        fresh.score_rules = self.score_rules.clone();
+        fresh.extra_life_rules = self.extra_life_rules.clone();
+        fresh.power_up_rules = self.power_up_rules.clone();
+        fresh.paddle_bounce = self.paddle_bounce.clone();
+        fresh.fire_was_down = self.fire_was_down;
+        fresh.pause_was_down = self.pause_was_down;
+        *self = fresh;
     }
 
     fn execute_movement(&mut self, current_timestamp_sec: f64, last_update_time_sec: f64) {
-        let time_delta = (current_timestamp_sec - last_update_time_sec) as f32;
+        let max_catch_up_sec = GameState::FIXED_STEP_SEC * GameState::MAX_STEPS_PER_TICK as f64;
+        self.accumulator_sec = f64::min(
+            self.accumulator_sec + (current_timestamp_sec - last_update_time_sec),
+            max_catch_up_sec
+        );
+
+        while self.accumulator_sec >= GameState::FIXED_STEP_SEC - GameState::STEP_EPSILON_SEC {
+            self.accumulator_sec = f64::max(self.accumulator_sec - GameState::FIXED_STEP_SEC, 0.0);
+            self.previous_frame = self.current_frame();
+            self.execute_step(GameState::FIXED_STEP_SEC as f32);
+            if !matches!(self.time_state, TimeState::Running{..}) {
+                self.accumulator_sec = 0.0;
+                break;
+            }
+        }
+
+        if let TimeState::Running{..} = self.time_state {
+            self.time_state = TimeState::Running {
+                last_update_time_sec: current_timestamp_sec
+            };
+        }
+    }
+
+    fn execute_step(&mut self, time_delta: f32) {
+        self.paddle.set_vector(FVector2d::new(GameState::PADDLE_SPEED * self.paddle_input_x, 0.0));
+        self.follow_paddle_target(time_delta);
+        let requested_paddle_speed = self.paddle.vector.x;
         self.paddle.advance(time_delta, self.field.left(), self.field.right());
+        self.carry_stuck_balls();
+        self.update_serve_aim(time_delta, requested_paddle_speed);
+        self.separate_balls_from_obstacles();
         self.handle_collisions(time_delta);
+        self.handle_ball_collisions();
+        self.handle_capsules(time_delta);
+        self.handle_laser(time_delta);
+        self.tick_effects(time_delta);
+        if self.handle_level_complete() {
+            return;
+        }
         self.handle_losing_ball();
         self.handle_game_over();
+    }
 
-        match self.time_state {
-            TimeState::Running{..} =>
-                self.time_state = TimeState::Running {
-                    last_update_time_sec: current_timestamp_sec
-                },
-            _ => (),
+    fn current_frame(&self) -> FrameSnapshot {
+        FrameSnapshot {
+            balls: self.balls.clone(),
+            paddle_position: self.paddle.position.clone(),
+        }
+    }
+
+    // Fraction of a fixed step accumulated since the last simulated step
+    pub fn interpolation_alpha(&self) -> f32 {
+        (self.accumulator_sec / GameState::FIXED_STEP_SEC) as f32
+    }
+
+    pub fn interpolated_frame(&self) -> FrameSnapshot {
+        let alpha = self.interpolation_alpha();
+        let balls = if self.previous_frame.balls.len() == self.balls.len() {
+            self.previous_frame.balls.iter().zip(self.balls.iter())
+                .map(|(previous, current)| Ball {
+                    position: Circle::new(previous.position.center.lerp(&current.position.center, alpha), current.position.radius),
+                    movement_vector: current.movement_vector.clone(),
+                    stuck_offset: current.stuck_offset,
+                })
+                .collect()
+        } else {
+            self.balls.clone()
+        };
+        FrameSnapshot {
+            balls,
+            paddle_position: self.previous_frame.paddle_position.lerp(&self.paddle.position, alpha),
+        }
+    }
+
+    fn carry_stuck_balls(&mut self) {
+        let paddle_center = self.paddle.position.center();
+        let half_width = self.paddle.position.width() / 2.0;
+        for ball in self.balls.iter_mut() {
+            if let Some(offset) = ball.stuck_offset {
+                let limited_offset = offset.clamp(-half_width, half_width);
+                ball.stuck_offset = Some(limited_offset);
+                ball.position.center = FPoint::new(paddle_center.x + limited_offset, self.paddle.position.top() + ball.position.radius);
+            }
+        }
+    }
+
+    // The aim indicator sweeps between the extreme serve angles while a ball is held
+    // Moving the paddle, by keys, stick or pointer, turns the aim while a ball
+    // is held. The requested speed counts, so a paddle at a wall still aims.
+    fn update_serve_aim(&mut self, time_delta: f32, requested_paddle_speed: f32) {
+        if !self.balls.iter().any(|ball| ball.is_stuck()) {
+            return;
         }
+        let turn = (requested_paddle_speed / GameState::PADDLE_SPEED).clamp(-1.0, 1.0);
+        self.serve_aim_angle = (self.serve_aim_angle + turn * GameState::SERVE_AIM_SPEED * time_delta)
+            .clamp(-GameState::MAX_SERVE_ANGLE, GameState::MAX_SERVE_ANGLE);
     }
 
-    fn handle_collisions(&mut self, time_delta: f32) -> () {
+    pub fn serve_vector(&self, speed: f32) -> FVector2d {
+        FVector2d::new(0.0, speed).rotate_clockwise(self.serve_aim_angle)
+    }
+
+    fn release_stuck_balls(&mut self) {
         for i in 0..self.balls.len() {
-            let adjusted_vector = self.balls[i].movement_vector.clone().mul_scalar(time_delta);
-            self.balls[i].position.center = self.balls[i].position.center.add(adjusted_vector);
-            let paddle_collision = self.has_ball_collided_with_paddle(&self.balls[i]).map(|collision| {
-                (collision, self.paddle.vector.clone())
-            });
-            let brick_collision = self.has_ball_collided_with_bricks(&self.balls[i]).map(|collision| {
-                self.bricks.swap_remove(collision.brick_index);
-                (collision.collision, FVector2d::zero())
-            });
-            let wall_collision = self.has_ball_coollided_with_wall(&self.balls[i]).map(|collision| {
-                (collision, FVector2d::zero())
-            });
-            let collistion_opt = paddle_collision.or(brick_collision).or(wall_collision);
-            match collistion_opt {
-                Some((collision, other_object_vector)) => {
-                    let new_movement_vector = new_vector_after_circle_collision(
-                        &self.balls[i].movement_vector,
-                        &collision,
-                        &other_object_vector
-                    );
-                    self.balls[i].movement_vector = new_movement_vector;
-                },
-                None => {},
+            if self.balls[i].is_stuck() {
+                self.balls[i].movement_vector = self.serve_vector(self.balls[i].movement_vector.length());
+                self.balls[i].stuck_offset = None;
+            }
+        }
+    }
+
+    fn handle_collisions(&mut self, time_delta: f32) {
+        for i in 0..self.balls.len() {
+            if self.balls[i].is_stuck() {
+                continue;
+            }
+            let mut time_left = time_delta;
+            for _ in 0..GameState::MAX_BOUNCES_PER_TICK {
+                let movement = self.balls[i].movement_vector.clone().mul_scalar(time_left * self.ball_speed_factor());
+                let contacts = self.first_contacts(&self.balls[i], &movement);
+                let Some(contact_time) = contacts.iter().map(|(swept_collision, _)| swept_collision.time).min_by(f32::total_cmp) else {
+                    self.balls[i].position.center = self.balls[i].position.center.add(movement);
+                    break;
+                };
+                let movement_to_contact = movement.mul_scalar(contact_time);
+                self.balls[i].position.center = self.balls[i].position.center.add(movement_to_contact);
+                let new_movement_vector = self.bounce_vector(&self.balls[i].movement_vector, &contacts);
+                let hit_paddle = contacts.iter().any(|(_, obstacle)| matches!(obstacle, Obstacle::Paddle));
+                let caught = self.has_effect(PowerUpKind::Catch) && contacts.iter().any(|(swept_collision, obstacle)| {
+                    matches!(obstacle, Obstacle::Paddle) && swept_collision.collision.normal.y > 0.0
+                });
+                if hit_paddle {
+                    self.combo_hits = 0;
+                    if !caught {
+                        self.paddle_hits += 1;
+                    }
+                }
+                let speed_up = contacts.iter().any(|(swept_collision, obstacle)| self.is_speed_up_hit(obstacle, &swept_collision.collision));
+                // Highest index first, removing a brick moves the last one into its slot
+                let mut hit_bricks: Vec<usize> = contacts.iter()
+                    .filter_map(|(_, obstacle)| match obstacle {
+                        Obstacle::Brick(brick_index) => Some(*brick_index),
+                        _ => None,
+                    })
+                    .collect();
+                hit_bricks.sort_unstable_by(|first, second| second.cmp(first));
+                for brick_index in hit_bricks {
+                    self.hit_brick(brick_index);
+                }
+                if caught {
+                    let offset = self.balls[i].position.center.x - self.paddle.position.center().x;
+                    self.balls[i].stuck_offset = Some(offset);
+                    break;
+                }
+                self.balls[i].movement_vector = if speed_up {
+                    self.speed_rules.speed_up(&new_movement_vector)
+                } else {
+                    self.speed_rules.limit(&new_movement_vector)
+                };
+                time_left *= 1.0 - contact_time;
+            }
+        }
+    }
+
+    // Pushes balls out of anything they overlap, like a paddle that moved into
+    // them. Bricks are looked up again after every push, since leaving one can
+    // land the ball in a neighbour. Walls go last so a squeezed ball stays
+    // inside the field, and a ball the wall pushed back into the upper half of
+    // the paddle leaves over its top.
+    fn separate_balls_from_obstacles(&mut self) {
+        for i in 0..self.balls.len() {
+            if self.balls[i].is_stuck() {
+                continue;
+            }
+            for _ in 0..GameState::MAX_SEPARATION_PASSES {
+                let ball = &self.balls[i].position;
+                let overlap = self.bricks_near(&swept_circle_bounds(ball, &FVector2d::zero()))
+                    .filter_map(|(_, brick)| circle_rectangle_collision(ball, &brick.position))
+                    .find(|collision| collision.depth > 0.0);
+                match overlap {
+                    Some(collision) => self.balls[i].position = push_out_of_collision(&self.balls[i].position, &collision),
+                    None => break,
+                }
+            }
+            if let Some(collision) = circle_rectangle_collision(&self.balls[i].position, &self.paddle.position) {
+                self.balls[i].position = push_out_of_collision(&self.balls[i].position, &collision);
+            }
+            for wall in self.wall_rectangles().iter() {
+                if let Some(collision) = circle_rectangle_collision(&self.balls[i].position, wall) {
+                    self.balls[i].position = push_out_of_collision(&self.balls[i].position, &collision);
+                }
             }
+            let ball = &self.balls[i].position;
+            let squeezed = circle_rectangle_collision(ball, &self.paddle.position).is_some_and(|collision| collision.depth > 0.0);
+            if squeezed && ball.center.y > self.paddle.position.center().y {
+                self.balls[i].position.center.y = self.paddle.position.top() + ball.radius;
+            }
+        }
+    }
+
+    // Everything the ball touches at the earliest contact time: several contacts
+    // only when it runs exactly into a seam between bricks or a corner of the field
+    fn first_contacts(&self, ball: &Ball, movement: &FVector2d) -> Vec<(SweptCollision, Obstacle)> {
+        let mut contacts: Vec<(SweptCollision, Obstacle)> = Vec::new();
+        if let Some(collision) = self.has_ball_collided_with_paddle(ball, movement) {
+            contacts.push((collision, Obstacle::Paddle));
+        }
+        contacts.extend(self.ball_brick_contacts(ball, movement).into_iter()
+            .map(|(brick_index, collision)| (collision, Obstacle::Brick(brick_index))));
+        contacts.extend(self.ball_wall_contacts(ball, movement)
+            .map(|collision| (collision, Obstacle::Wall)));
+        if let Some(first_time) = contacts.iter().map(|(swept_collision, _)| swept_collision.time).min_by(f32::total_cmp) {
+            contacts.retain(|(swept_collision, _)| swept_collision.time <= first_time);
+        }
+        contacts
+    }
+
+    // A flat face wins over corners, so a ball at the seam between two bricks
+    // bounces like off one long face. Flat faces are applied one after the
+    // other, which reflects both components in an inner corner. Corners alone
+    // reflect off their averaged normal.
+    fn bounce_vector(&self, ball_vector: &FVector2d, contacts: &[(SweptCollision, Obstacle)]) -> FVector2d {
+        let flat_contacts: Vec<&(SweptCollision, Obstacle)> = contacts.iter()
+            .filter(|(swept_collision, _)| swept_collision.collision.is_flat())
+            .collect();
+        if !flat_contacts.is_empty() {
+            return flat_contacts.iter().fold(ball_vector.clone(), |vector, (swept_collision, obstacle)| {
+                // An earlier face may already have sent the ball away from this one
+                if vector.dot_product(&swept_collision.collision.normal) >= 0.0 {
+                    return vector;
+                }
+                self.bounce_off(&vector, &swept_collision.collision, obstacle)
+            });
+        }
+        let (first_collision, first_obstacle) = &contacts[0];
+        let normal = contacts.iter().fold(FVector2d::zero(), |sum, (swept_collision, _)| {
+            sum.plus(&swept_collision.collision.normal.clone().normalize())
+        });
+        if contacts.len() == 1 || normal.is_zero() {
+            return self.bounce_off(ball_vector, &first_collision.collision, first_obstacle);
         }
+        let depth = contacts.iter().map(|(swept_collision, _)| swept_collision.collision.depth).fold(0.0, f32::max);
+        let collision = Collision { point: first_collision.collision.point.clone(), normal, kind: CollisionKind::Corner, depth };
+        self.bounce_off(ball_vector, &collision, first_obstacle)
     }
 
-    fn handle_losing_ball(&mut self) -> () {
+    fn bounce_off(&self, ball_vector: &FVector2d, collision: &Collision, obstacle: &Obstacle) -> FVector2d {
+        match obstacle {
+            Obstacle::Paddle => self.paddle_bounce_vector(ball_vector, collision),
+            _ => new_vector_after_circle_collision(ball_vector, collision),
+        }
+    }
+
+    fn is_speed_up_hit(&self, obstacle: &Obstacle, collision: &Collision) -> bool {
+        match obstacle {
+            Obstacle::Paddle => {
+                let hits_per_speed_up = self.speed_rules.paddle_hits_per_speed_up;
+                hits_per_speed_up > 0 && self.paddle_hits.is_multiple_of(hits_per_speed_up)
+            },
+            // Only the top wall pushes the ball down
+            Obstacle::Wall => self.speed_rules.speed_up_on_top_wall && collision.normal.y < 0.0,
+            Obstacle::Brick(_) => false,
+        }
+    }
+
+    fn handle_losing_ball(&mut self) {
         self.balls.retain_mut(|ball| {
            !GameState::has_ball_left_screen(ball)
         });
-        if self.balls.len() == 0 {
+        if self.balls.is_empty() {
             self.lives_left -= 1;
+            self.combo_hits = 0;
+            self.paddle_hits = 0;
+            self.reset_power_ups();
             self.paddle = GameState::initial_paddle_state();
-            self.balls = GameState::initial_balls_state();
-            self.time_state = TimeState::Stopped;
+            self.balls = GameState::initial_balls_state(self.speed_rules.serve_speed);
+            self.previous_frame = self.current_frame();
+            self.time_state = TimeState::LifeLost;
         }
     }
 
-    fn handle_game_over(&mut self) -> () {
-        if self.lives_left == 0 {
-            self.time_state = TimeState::GameOver;
+    pub fn bricks(&self) -> &[Brick] {
+        &self.bricks
+    }
+
+    pub fn set_bricks(&mut self, bricks: Vec<Brick>) {
+        self.brick_grid = SpatialGrid::with_items(&self.field, GameState::BRICK_GRID_CELL_SIZE, bricks.iter().map(|brick| &brick.position));
+        self.bricks = bricks;
+    }
+
+    pub fn add_brick(&mut self, brick: Brick) {
+        self.brick_grid.insert(self.bricks.len(), &brick.position);
+        self.bricks.push(brick);
+    }
+
+    #[cfg(feature = "serde")]
+    fn rebuild_brick_grid(&mut self) {
+        let bricks = std::mem::take(&mut self.bricks);
+        self.set_bricks(bricks);
+    }
+
+    fn remove_brick(&mut self, brick_index: usize) -> Brick {
+        let last_position = &self.bricks[self.bricks.len() - 1].position;
+        self.brick_grid.swap_remove(brick_index, &self.bricks[brick_index].position, last_position);
+        self.bricks.swap_remove(brick_index)
+    }
+
+    // Candidates come in index order, so ties resolve like a scan over all bricks
+    fn bricks_near(&self, area: &Rectangle) -> impl Iterator<Item = (usize, &Brick)> {
+        debug_assert_eq!(self.brick_grid.len(), self.bricks.len(), "bricks changed without updating brick_grid");
+        self.brick_grid.query(area).into_iter().map(|index| (index, &self.bricks[index]))
+    }
+
+    fn hit_brick(&mut self, brick_index: usize) {
+        let hit = self.bricks[brick_index].hit();
+        let points = self.score_rules.points_for(&self.bricks[brick_index].variety, &hit);
+        if hit == BrickHit::Deflected {
+            self.add_score(points);
+            return;
+        }
+        self.add_score(points * self.score_rules.combo_multiplier(self.combo_hits));
+        self.combo_hits += 1;
+        if hit == BrickHit::Destroyed {
+            let brick = self.remove_brick(brick_index);
+            self.maybe_drop_capsule(&brick);
+        }
+    }
+
+    fn maybe_drop_capsule(&mut self, brick: &Brick) {
+        if self.random.next_f32() >= self.power_up_rules.drop_chance {
+            return;
+        }
+        let kind = PowerUpKind::ALL[self.random.next_index(PowerUpKind::ALL.len())];
+        self.capsules.push(Capsule::new(&brick.position.center(), kind));
+    }
+
+    fn handle_capsules(&mut self, time_delta: f32) {
+        let fall_vector = FVector2d::new(0.0, -self.power_up_rules.fall_speed * time_delta);
+        for capsule in self.capsules.iter_mut() {
+            capsule.position = capsule.position.advance(&fall_vector);
+        }
+        let (caught, falling): (Vec<Capsule>, Vec<Capsule>) = self.capsules.drain(..)
+            .partition(|capsule| capsule.position.intersects(&self.paddle.position));
+        self.capsules = falling.into_iter()
+            .filter(|capsule| capsule.position.top() > self.field.bottom())
+            .collect();
+        for capsule in caught {
+            self.apply_power_up(capsule.kind);
+        }
+    }
+
+    fn apply_power_up(&mut self, kind: PowerUpKind) {
+        if !kind.is_timed() {
+            self.split_balls();
+            return;
         }
+        let opposite_kind = match kind {
+            PowerUpKind::Expand => Some(PowerUpKind::Shrink),
+            PowerUpKind::Shrink => Some(PowerUpKind::Expand),
+            PowerUpKind::Slow | PowerUpKind::Catch | PowerUpKind::Laser | PowerUpKind::Multiball => None,
+        };
+        self.active_effects.retain(|effect| effect.kind != kind && Some(effect.kind) != opposite_kind);
+        self.active_effects.push(ActiveEffect {
+            kind,
+            time_left_sec: self.power_up_rules.effect_duration_sec,
+        });
+        self.update_paddle_width();
+    }
+
+    fn split_balls(&mut self) {
+        let spread_angle = self.power_up_rules.multiball_spread_angle;
+        let mut split_balls = Vec::with_capacity(self.balls.len() * 3);
+        for ball in self.balls.iter() {
+            // Held balls are served right away, copies held on the same spot would move as one
+            let movement_vector = if ball.is_stuck() {
+                self.serve_vector(ball.movement_vector.length())
+            } else {
+                ball.movement_vector.clone()
+            };
+            for angle in [-spread_angle, 0.0, spread_angle] {
+                split_balls.push(Ball {
+                    position: ball.position.clone(),
+                    movement_vector: movement_vector.clone().rotate_clockwise(angle),
+                    stuck_offset: None,
+                });
+            }
+        }
+        self.balls = split_balls;
     }
 
-    fn has_ball_collided_with_bricks(&self, ball: &Ball) -> Option<BallCollision> {
-        for (index, brick) in self.bricks.iter().enumerate() {
-            let collision_opt = circle_rectangle_collision(&ball.position, &brick.position);
-            match collision_opt {
-                Some(collision) => {
-                    return Some(BallCollision::new(ball.clone(), index, collision));
+    fn handle_ball_collisions(&mut self) {
+        for i in 0..self.balls.len() {
+            for j in i + 1..self.balls.len() {
+                if self.balls[i].is_stuck() || self.balls[j].is_stuck() {
+                    continue;
+                }
+                let Some(collision) = circle_circle_collision(&self.balls[i].position, &self.balls[j].position) else {
+                    continue;
+                };
+                let new_vectors_opt = new_vectors_after_circles_collision(
+                    &self.balls[i].movement_vector,
+                    &self.balls[j].movement_vector,
+                    &collision
+                );
+                if let Some((first_vector, second_vector)) = new_vectors_opt {
+                    // Freshly split multiballs overlap while moving apart, only approaching balls are separated
+                    let half_overlap = Collision { depth: collision.depth / 2.0, ..collision.clone() };
+                    self.balls[i].position = push_out_of_collision(&self.balls[i].position, &half_overlap);
+                    let other_half = Collision { normal: collision.normal.clone().invert(), ..half_overlap };
+                    self.balls[j].position = push_out_of_collision(&self.balls[j].position, &other_half);
+                    self.balls[i].movement_vector = self.speed_rules.limit(&first_vector);
+                    self.balls[j].movement_vector = self.speed_rules.limit(&second_vector);
                 }
-                None => {}
             }
         }
-        None
-    }
-
-    fn has_ball_coollided_with_wall(&self, ball: &Ball) -> Option<Collision> {
-        if (ball.position.center.x - ball.position.radius) < self.field.left() {
-            let collistion_point = FPoint::new(self.field.left(), ball.position.center.y);
-            let normal = FVector2d::between(&collistion_point, &ball.position.center);
-            Some(Collision {
-                point: collistion_point,
-                normal,
-            })
-        } else if (ball.position.center.x + ball.position.radius) > self.field.right() {
-            let collistion_point = FPoint::new(self.field.right(), ball.position.center.y);
-            let normal = FVector2d::between(&collistion_point, &ball.position.center);
-            Some(Collision {
-                point: collistion_point,
-                normal,
-            })
-        } else if (ball.position.center.y + ball.position.radius) > self.field.top() {
-            let collistion_point = FPoint::new(ball.position.center.x, self.field.top());
-            let normal = FVector2d::between(&collistion_point, &ball.position.center);
-            Some(Collision {
-                point: collistion_point,
-                normal,
-            })
+    }
+
+    fn tick_effects(&mut self, time_delta: f32) {
+        let effects_before = self.active_effects.len();
+        let had_catch = self.has_effect(PowerUpKind::Catch);
+        for effect in self.active_effects.iter_mut() {
+            effect.time_left_sec -= time_delta;
+        }
+        self.active_effects.retain(|effect| effect.time_left_sec > 0.0);
+        if self.active_effects.len() != effects_before {
+            self.update_paddle_width();
+        }
+        if had_catch && !self.has_effect(PowerUpKind::Catch) {
+            self.release_stuck_balls();
+        }
+    }
+
+    pub fn has_effect(&self, kind: PowerUpKind) -> bool {
+        self.active_effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    // The velocity that reaches the pointer in one step, so bounces still get
+    // a paddle vector that matches how the paddle actually moved
+    fn follow_paddle_target(&mut self, time_delta: f32) {
+        if let Some(target_x) = self.paddle_target_x {
+            let speed = (target_x - self.paddle.position.center().x) / time_delta;
+            let max_speed = GameState::POINTER_PADDLE_MAX_SPEED;
+            self.paddle.set_vector(FVector2d::new(speed.clamp(-max_speed, max_speed), 0.0));
+        }
+    }
+
+    fn update_paddle_width(&mut self) {
+        let width_factor = if self.has_effect(PowerUpKind::Expand) {
+            self.power_up_rules.expand_factor
+        } else if self.has_effect(PowerUpKind::Shrink) {
+            self.power_up_rules.shrink_factor
+        } else {
+            1.0
+        };
+        self.paddle.set_width(GameState::PADDLE_WIDTH * width_factor, self.field.left(), self.field.right());
+    }
+
+    fn ball_speed_factor(&self) -> f32 {
+        if self.has_effect(PowerUpKind::Slow) {
+            self.power_up_rules.slow_factor
         } else {
-            None
+            1.0
         }
     }
 
-    fn has_ball_collided_with_paddle(&self, ball: &Ball) -> Option<Collision> {
-        circle_rectangle_collision(&ball.position, &self.paddle.position).map(|collision| {
-            let paddle_width = self.paddle.position.right() - self.paddle.position.left();
-            let x_coord_relative_paddle_center = (collision.point.x - &self.paddle.position.left()) - (paddle_width / 2.0);
-            let paddle_horisontal_vector = FVector2d::new(self.paddle.position.right() - self.paddle.position.left(), 0.0);
-            let angle_rotation = if collision.point.y == self.paddle.position.top() && collision.normal.dot_product(&paddle_horisontal_vector).abs() < f32::EPSILON  {
-                std::f32::consts::FRAC_PI_8 * (x_coord_relative_paddle_center / paddle_width)
-            } else {
-                0.0
-            };
-            Collision {
-                point: collision.point,
-                normal: collision.normal.rotate_clockwise(angle_rotation)
+    fn handle_laser(&mut self, time_delta: f32) {
+        self.laser_cooldown_left_sec = f32::max(self.laser_cooldown_left_sec - time_delta, 0.0);
+        if self.fire_was_down && self.has_effect(PowerUpKind::Laser) && self.laser_cooldown_left_sec <= 0.0 {
+            let top = self.paddle.position.top();
+            let inset = Projectile::WIDTH;
+            self.projectiles.push(Projectile::new(&FPoint::new(self.paddle.position.left() + inset, top)));
+            self.projectiles.push(Projectile::new(&FPoint::new(self.paddle.position.right() - inset, top)));
+            self.laser_cooldown_left_sec = self.power_up_rules.laser_cooldown_sec;
+        }
+
+        let bolt_vector = FVector2d::new(0.0, self.power_up_rules.laser_speed * time_delta);
+        let mut remaining_projectiles = Vec::with_capacity(self.projectiles.len());
+        for projectile in std::mem::take(&mut self.projectiles) {
+            let moved = Projectile { position: projectile.position.advance(&bolt_vector) };
+            let hit_brick_index = self.bricks_near(&moved.position)
+                .filter(|(_, brick)| brick.position.intersects(&moved.position))
+                .min_by(|(_, first), (_, second)| first.position.bottom().total_cmp(&second.position.bottom()))
+                .map(|(index, _)| index);
+            match hit_brick_index {
+                Some(brick_index) => self.hit_brick(brick_index),
+                None if moved.position.bottom() < self.field.top() => remaining_projectiles.push(moved),
+                None => (),
             }
-        })
+        }
+        self.projectiles = remaining_projectiles;
+    }
+
+    fn reset_power_ups(&mut self) {
+        self.capsules.clear();
+        self.active_effects.clear();
+        self.projectiles.clear();
+        self.laser_cooldown_left_sec = 0.0;
+    }
+
+    fn add_score(&mut self, points: i32) {
+        self.score += points;
+        let mut last_threshold = self.extra_lives_awarded.checked_sub(1)
+            .and_then(|extra_life_number| self.extra_life_rules.threshold(extra_life_number));
+        while let Some(threshold) = self.extra_life_rules.threshold(self.extra_lives_awarded) {
+            // Thresholds that stop growing would award lives forever
+            if self.score < threshold || last_threshold.is_some_and(|last| threshold <= last) {
+                break;
+            }
+            last_threshold = Some(threshold);
+            self.extra_lives_awarded += 1;
+            self.lives_left = i32::min(self.lives_left + 1, self.extra_life_rules.max_lives);
+        }
+    }
+
+    pub fn all_breakable_bricks_removed(&self) -> bool {
+        !self.bricks.iter().any(|brick| brick.is_breakable())
+    }
+
+    fn handle_level_complete(&mut self) -> bool {
+        if !self.all_breakable_bricks_removed() {
+            return false;
+        }
+        self.add_score(self.score_rules.level_clear_bonus);
+        self.combo_hits = 0;
+        self.time_state = if self.current_level + 1 < self.levels.len() {
+            TimeState::LevelComplete
+        } else {
+            TimeState::Victory
+        };
+        true
+    }
+
+    fn start_next_level(&mut self) {
+        self.current_level += 1;
+        self.field = self.levels[self.current_level].field.clone();
+        self.set_bricks(self.levels[self.current_level].bricks.clone());
+        self.speed_rules = self.levels[self.current_level].speed_rules.clone();
+        self.paddle_hits = 0;
+        self.reset_power_ups();
+        self.paddle = GameState::initial_paddle_state();
+        self.balls = GameState::initial_balls_state(self.speed_rules.serve_speed);
+        self.previous_frame = self.current_frame();
+        self.time_state = TimeState::Stopped;
+    }
+
+    fn handle_game_over(&mut self) {
+        if self.lives_left == 0 {
+            self.time_state = TimeState::GameOver;
+        }
+    }
+
+    fn ball_brick_contacts(&self, ball: &Ball, movement: &FVector2d) -> Vec<(usize, SweptCollision)> {
+        let nearby_bricks = self.bricks_near(&swept_circle_bounds(&ball.position, movement))
+            .map(|(index, brick)| (index, &brick.position));
+        swept_collisions(&ball.position, movement, nearby_bricks)
+    }
+
+    fn ball_wall_contacts<'a>(&self, ball: &'a Ball, movement: &'a FVector2d) -> impl Iterator<Item = SweptCollision> + 'a {
+        self.wall_rectangles().into_iter()
+            .filter_map(|wall| swept_circle_rectangle_collision(&ball.position, movement, &wall))
+    }
+
+    fn wall_rectangles(&self) -> [Rectangle; 3] {
+        let thickness = GameState::WALL_THICKNESS;
+        [
+            Rectangle::make_by_coords(self.field.left() - thickness, self.field.bottom() - thickness, self.field.left(), self.field.top() + thickness),
+            Rectangle::make_by_coords(self.field.right(), self.field.bottom() - thickness, self.field.right() + thickness, self.field.top() + thickness),
+            Rectangle::make_by_coords(self.field.left() - thickness, self.field.top(), self.field.right() + thickness, self.field.top() + thickness),
+        ]
+    }
+
+    fn has_ball_collided_with_paddle(&self, ball: &Ball, movement: &FVector2d) -> Option<SweptCollision> {
+        swept_circle_rectangle_collision(&ball.position, movement, &self.paddle.position)
+    }
+
+    fn paddle_bounce_vector(&self, ball_vector: &FVector2d, collision: &Collision) -> FVector2d {
+        let is_top_face_hit = collision.is_flat() && collision.point.y == self.paddle.position.top();
+        if !is_top_face_hit {
+            return new_vector_after_circle_collision(ball_vector, collision);
+        }
+        let hit_offset = (collision.point.x - self.paddle.position.center().x) / (self.paddle.position.width() / 2.0);
+        self.paddle_bounce.outgoing_vector(ball_vector, hit_offset, &self.paddle.vector)
     }
 
     fn has_ball_left_screen(ball: &Ball) -> bool {
@@ -0,0 +1,840 @@
+use crate::game_state::*;
+use crate::geometry::*;
+use crate::level::*;
+use crate::paddle_bounce::*;
+use crate::power_up::*;
+
+fn running_game_with_ball(center: FPoint, movement_vector: FVector2d) -> GameState {
+    let mut game = GameState::make_initial();
+    game.balls = vec![Ball {
+        position: Circle::new(center, 4.0),
+        movement_vector,
+        stuck_offset: None,
+    }];
+    game.time_state = TimeState::Running { last_update_time_sec: 0.0 };
+    game
+}
+
+#[test]
+fn fast_ball_does_not_tunnel_through_brick() {
+    let mut game = running_game_with_ball(FPoint::new(30.0, 300.0), FVector2d::new(0.0, 40000.0));
+    let bricks_before = game.bricks().len();
+
+    game.tick(GameState::FIXED_STEP_SEC, KeyboardState::none());
+
+    assert_eq!(game.bricks().len(), bricks_before - 1);
+    assert!(game.balls[0].movement_vector.y < 0.0);
+    assert!(game.balls[0].position.center.y < 400.0);
+}
+
+fn steel_brick_at(x1: f32, y1: f32, x2: f32, y2: f32) -> Brick {
+    Brick::new(x1, y1, x2, y2, BrickVariety::Steel)
+}
+
+#[test]
+fn ball_bounces_off_steel_brick_without_breaking_it() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    game.set_bricks(vec![
+        steel_brick_at(180.0, 210.0, 220.0, 220.0),
+        Brick::new(10.0, 420.0, 50.0, 440.0, BrickVariety::standard(1)),
+    ]);
+
+    for tick in 1..=20 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, KeyboardState::none());
+    }
+
+    assert_eq!(game.bricks().len(), 2);
+    assert!(game.balls[0].movement_vector.y < 0.0);
+    assert!(game.balls[0].position.center.y + game.balls[0].position.radius <= 210.0);
+}
+
+#[test]
+fn steel_bricks_are_ignored_when_checking_for_cleared_field() {
+    let mut game = GameState::make_initial();
+    assert!(!game.all_breakable_bricks_removed());
+
+    let unbreakable_bricks = game.bricks().iter().filter(|brick| !brick.is_breakable()).cloned().collect();
+    game.set_bricks(unbreakable_bricks);
+    game.add_brick(steel_brick_at(180.0, 210.0, 220.0, 220.0));
+
+    assert!(game.all_breakable_bricks_removed());
+}
+
+#[test]
+fn ball_bounces_several_times_in_one_tick() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 300.0), FVector2d::new(-120000.0, 0.0));
+    game.speed_rules = SpeedRules { min_speed: 0.0, max_speed: f32::INFINITY, min_vertical_angle: 0.0, ..SpeedRules::default() };
+
+    game.tick(GameState::FIXED_STEP_SEC, KeyboardState::none());
+
+    let ball = &game.balls[0];
+    assert!(ball.movement_vector.x < 0.0);
+    assert!(ball.position.center.x > game.field.left() && ball.position.center.x < game.field.right());
+}
+
+#[test]
+fn fixed_step_results_do_not_depend_on_tick_rate() {
+    let mut coarse = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(70.0, 130.0));
+    let mut fine = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(70.0, 130.0));
+
+    for tick in 1..=50 {
+        coarse.tick(tick as f64 * 0.04, KeyboardState::none());
+    }
+    for tick in 1..=400 {
+        fine.tick(tick as f64 * 0.005, KeyboardState::none());
+    }
+
+    assert!(coarse.balls[0].position.center.is_same(&fine.balls[0].position.center, 10e-3),
+            "Coarse: {:?}. Fine: {:?}", coarse.balls[0], fine.balls[0]);
+}
+
+#[test]
+fn catch_up_is_capped_after_stall() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 10.0));
+
+    game.tick(60.0, KeyboardState::none());
+
+    let max_travel = 10.0 * GameState::FIXED_STEP_SEC as f32 * GameState::MAX_STEPS_PER_TICK as f32;
+    assert!((game.balls[0].position.center.y - (200.0 + max_travel)).abs() < 10e-3);
+    assert!(game.interpolation_alpha() < 1.0);
+}
+
+#[test]
+fn interpolated_frame_between_steps() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+
+    game.tick(GameState::FIXED_STEP_SEC * 1.5, KeyboardState::none());
+
+    let frame = game.interpolated_frame();
+    let step_travel = 100.0 * GameState::FIXED_STEP_SEC as f32;
+    assert!((frame.balls[0].position.center.y - (200.0 + step_travel * 0.5)).abs() < 10e-3);
+}
+
+#[test]
+fn multi_hit_brick_loses_hit_points_until_destroyed() {
+    let mut brick = Brick::new(0.0, 0.0, 40.0, 20.0, BrickVariety::Standard { color: 1 }).with_hit_points(3);
+
+    assert_eq!(brick.hit(), BrickHit::Damaged);
+    assert_eq!(brick.hit(), BrickHit::Damaged);
+    assert_eq!(brick.damage_taken(), 2);
+    assert_eq!(brick.hit(), BrickHit::Destroyed);
+}
+
+#[test]
+fn damaging_and_destroying_bricks_are_scored_separately() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    game.set_bricks(vec![Brick::new(180.0, 210.0, 220.0, 220.0, BrickVariety::Standard { color: 1 }).with_hit_points(2)]);
+
+    for tick in 1..=20 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, KeyboardState::none());
+    }
+
+    assert_eq!(game.bricks().len(), 1);
+    assert_eq!(game.bricks()[0].hit_points, 1);
+    let rules = ScoreRules::default();
+    assert_eq!(game.score, rules.standard_hit);
+
+    game.balls[0].movement_vector = FVector2d::new(0.0, 100.0);
+    for tick in 21..=40 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, KeyboardState::none());
+    }
+
+    assert!(game.bricks().is_empty());
+    let combo_multiplier = 2;
+    assert_eq!(game.score, rules.standard_hit + (rules.standard_hit + rules.standard_destroyed) * combo_multiplier + rules.level_clear_bonus);
+}
+
+fn single_brick_level(name: &str) -> Level {
+    Level::new(name, vec![Brick::new(180.0, 210.0, 220.0, 220.0, BrickVariety::standard(1))])
+}
+
+fn clear_current_level(game: &mut GameState, start_tick: i32) -> i32 {
+    game.balls = vec![Ball {
+        position: Circle::new(FPoint::new(200.0, 200.0), 4.0),
+        movement_vector: FVector2d::new(0.0, 100.0),
+        stuck_offset: None,
+    }];
+    game.time_state = TimeState::Running { last_update_time_sec: start_tick as f64 * GameState::FIXED_STEP_SEC };
+    for tick in start_tick + 1..=start_tick + 20 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, KeyboardState::none());
+    }
+    start_tick + 20
+}
+
+#[test]
+fn clearing_a_level_advances_to_the_next_one_keeping_lives() {
+    let mut game = GameState::with_levels(vec![single_brick_level("First"), single_brick_level("Second")]).unwrap();
+    game.lives_left = 2;
+
+    let tick = clear_current_level(&mut game, 0);
+    assert_eq!(game.time_state, TimeState::LevelComplete);
+
+    let fire = KeyboardState { fire: true, ..KeyboardState::none() };
+    game.tick((tick + 1) as f64 * GameState::FIXED_STEP_SEC, fire);
+
+    assert_eq!(game.time_state, TimeState::Stopped);
+    assert_eq!(game.current_level, 1);
+    assert_eq!(game.bricks().len(), 1);
+    assert_eq!(game.lives_left, 2);
+    assert!(game.balls[0].position.center.is_same(&FPoint::new(75.0, 44.0), 10e-6));
+}
+
+#[test]
+fn game_needs_at_least_one_level() {
+    assert!(GameState::with_levels(Vec::new()).is_none());
+}
+
+#[test]
+fn clearing_the_last_level_is_a_victory() {
+    let mut game = GameState::with_levels(vec![single_brick_level("Only")]).unwrap();
+
+    clear_current_level(&mut game, 0);
+
+    assert_eq!(game.time_state, TimeState::Victory);
+}
+
+#[test]
+fn combo_multiplier_grows_with_consecutive_hits_and_is_capped() {
+    let rules = ScoreRules::default();
+
+    assert_eq!(rules.combo_multiplier(0), 1);
+    assert_eq!(rules.combo_multiplier(2), 3);
+    assert_eq!(rules.combo_multiplier(100), rules.max_combo_multiplier);
+}
+
+#[test]
+fn touching_the_paddle_resets_combo() {
+    let mut game = running_game_with_ball(FPoint::new(75.0, 60.0), FVector2d::new(0.0, -100.0));
+    game.combo_hits = 4;
+
+    for tick in 1..=50 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, KeyboardState::none());
+    }
+
+    assert!(game.balls[0].movement_vector.y > 0.0);
+    assert_eq!(game.combo_hits, 0);
+}
+
+#[test]
+fn extra_life_thresholds_repeat_after_the_list() {
+    let rules = ExtraLifeRules { thresholds: vec![100, 300], then_every: Some(500), max_lives: 5 };
+
+    assert_eq!(rules.threshold(0), Some(100));
+    assert_eq!(rules.threshold(1), Some(300));
+    assert_eq!(rules.threshold(2), Some(800));
+    assert_eq!(rules.threshold(3), Some(1300));
+    assert_eq!(ExtraLifeRules { then_every: None, ..rules.clone() }.threshold(2), None);
+    assert_eq!(ExtraLifeRules { then_every: Some(0), ..rules.clone() }.threshold(2), None);
+    assert_eq!(ExtraLifeRules { then_every: Some(-100), ..rules }.threshold(2), None);
+}
+
+#[test]
+fn zero_step_after_the_thresholds_awards_no_endless_lives() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    game.add_brick(Brick::new(180.0, 210.0, 220.0, 220.0, BrickVariety::standard(1)));
+    game.extra_life_rules = ExtraLifeRules { thresholds: vec![10], then_every: Some(0), max_lives: 9 };
+    game.score = 15;
+
+    run_ticks(&mut game, 1, 20);
+
+    assert!(game.score > 15);
+    assert_eq!(game.extra_lives_awarded, 1);
+}
+
+#[test]
+fn thresholds_that_stop_growing_award_one_life() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    game.add_brick(Brick::new(180.0, 210.0, 220.0, 220.0, BrickVariety::standard(1)));
+    game.extra_life_rules = ExtraLifeRules { thresholds: vec![10, 10, 5], then_every: None, max_lives: 9 };
+
+    run_ticks(&mut game, 1, 20);
+
+    assert_eq!(game.extra_lives_awarded, 1);
+}
+
+#[test]
+fn crossing_score_thresholds_awards_extra_lives_up_to_the_cap() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    game.add_brick(Brick::new(180.0, 210.0, 220.0, 220.0, BrickVariety::standard(1)));
+    game.extra_life_rules = ExtraLifeRules { thresholds: vec![10, 20, 30], then_every: None, max_lives: 4 };
+    game.lives_left = 3;
+    game.score = 15;
+
+    for tick in 1..=20 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, KeyboardState::none());
+    }
+
+    assert_eq!(game.extra_lives_awarded, 3);
+    assert_eq!(game.lives_left, 4);
+}
+
+fn run_ticks(game: &mut GameState, from_tick: i32, to_tick: i32) {
+    for tick in from_tick..=to_tick {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, KeyboardState::none());
+    }
+}
+
+#[test]
+fn destroyed_brick_drops_capsule_by_chance() {
+    let mut always = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    always.add_brick(Brick::new(180.0, 210.0, 220.0, 220.0, BrickVariety::standard(1)));
+    always.power_up_rules.drop_chance = 1.0;
+    let mut never = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    never.add_brick(Brick::new(180.0, 210.0, 220.0, 220.0, BrickVariety::standard(1)));
+    never.power_up_rules.drop_chance = 0.0;
+
+    run_ticks(&mut always, 1, 20);
+    run_ticks(&mut never, 1, 20);
+
+    assert_eq!(always.capsules.len(), 1);
+    assert!(always.capsules[0].position.center().x > 190.0 && always.capsules[0].position.center().x < 210.0);
+    assert!(never.capsules.is_empty());
+}
+
+#[test]
+fn caught_expand_capsule_widens_paddle_until_effect_expires() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 10.0));
+    game.capsules.push(Capsule::new(&FPoint::new(75.0, 45.0), PowerUpKind::Expand));
+    game.power_up_rules.effect_duration_sec = 0.5;
+    let width_before = game.paddle.position.width();
+
+    run_ticks(&mut game, 1, 40);
+
+    assert!(game.capsules.is_empty());
+    assert!(game.has_effect(PowerUpKind::Expand));
+    assert!((game.paddle.position.width() - width_before * game.power_up_rules.expand_factor).abs() < 10e-3);
+
+    run_ticks(&mut game, 41, 140);
+
+    assert!(!game.has_effect(PowerUpKind::Expand));
+    assert!((game.paddle.position.width() - width_before).abs() < 10e-3);
+}
+
+#[test]
+fn missed_capsule_is_removed_below_the_field() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 10.0));
+    game.capsules.push(Capsule::new(&FPoint::new(300.0, 30.0), PowerUpKind::Slow));
+
+    run_ticks(&mut game, 1, 100);
+
+    assert!(game.capsules.is_empty());
+    assert!(game.active_effects.is_empty());
+}
+
+#[test]
+fn slow_effect_reduces_ball_travel() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    game.active_effects.push(ActiveEffect { kind: PowerUpKind::Slow, time_left_sec: 10.0 });
+
+    run_ticks(&mut game, 1, 10);
+
+    let expected_travel = 100.0 * 10.0 * GameState::FIXED_STEP_SEC as f32 * game.power_up_rules.slow_factor;
+    assert!((game.balls[0].position.center.y - (200.0 + expected_travel)).abs() < 10e-3);
+}
+
+#[test]
+fn multiball_splits_every_ball_into_three_diverging_balls() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    game.capsules.push(Capsule::new(&FPoint::new(75.0, 45.0), PowerUpKind::Multiball));
+
+    run_ticks(&mut game, 1, 40);
+
+    assert_eq!(game.balls.len(), 3);
+    assert!(game.active_effects.is_empty());
+    let speeds: Vec<f32> = game.balls.iter().map(|ball| ball.movement_vector.length()).collect();
+    assert!(speeds.iter().all(|speed| (speed - 100.0).abs() < 10e-3));
+    assert!(game.balls[0].movement_vector.x < 0.0 && game.balls[2].movement_vector.x > 0.0);
+}
+
+#[test]
+fn approaching_balls_bounce_off_each_other() {
+    let mut game = running_game_with_ball(FPoint::new(150.0, 200.0), FVector2d::new(100.0, 0.0));
+    game.balls.push(Ball {
+        position: Circle::new(FPoint::new(250.0, 200.0), 4.0),
+        movement_vector: FVector2d::new(-100.0, 0.0),
+        stuck_offset: None,
+    });
+
+    run_ticks(&mut game, 1, 100);
+
+    assert!(game.balls[0].movement_vector.x < 0.0);
+    assert!(game.balls[1].movement_vector.x > 0.0);
+    assert!(game.balls[0].position.center.x < game.balls[1].position.center.x);
+}
+
+fn key_state(move_left: bool, move_right: bool, fire: bool) -> KeyboardState {
+    KeyboardState { move_left, move_right, fire, ..KeyboardState::none() }
+}
+
+#[test]
+fn served_ball_rides_on_paddle_until_fire() {
+    let mut game = GameState::make_initial();
+
+    for tick in 1..=20 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, key_state(false, true, false));
+    }
+
+    let ball = &game.balls[0];
+    assert!(ball.is_stuck());
+    assert!((ball.position.center.x - game.paddle.position.center().x).abs() < 10e-3);
+    assert!((ball.position.center.y - (game.paddle.position.top() + ball.position.radius)).abs() < 10e-3);
+    assert!(game.paddle.position.left() > 50.0);
+}
+
+#[test]
+fn moving_the_paddle_turns_the_serve_aim() {
+    let mut game = GameState::make_initial();
+    run_ticks(&mut game, 1, 20);
+    assert_eq!(game.serve_aim_angle, 0.0);
+
+    for tick in 21..=40 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, key_state(false, true, false));
+    }
+    let aimed_right = game.serve_aim_angle;
+    assert!(aimed_right > 0.0);
+
+    let pointer_x = game.paddle.position.center().x - 50.0;
+    game.tick(41.0 * GameState::FIXED_STEP_SEC, KeyboardState { pointer_x: Some(pointer_x), ..KeyboardState::none() });
+    assert!(game.serve_aim_angle < aimed_right);
+
+    for tick in 42..=1000 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, key_state(false, true, false));
+    }
+    assert_eq!(game.paddle.position.right(), game.field.right());
+    game.serve_aim_angle = 0.0;
+    for tick in 1001..=1020 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, key_state(false, true, false));
+    }
+    assert!((game.serve_aim_angle - aimed_right).abs() < 10e-4, "Actual: {}", game.serve_aim_angle);
+}
+
+#[test]
+fn fire_launches_held_ball_along_the_aim() {
+    let mut game = GameState::make_initial();
+    for tick in 1..=30 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, key_state(true, false, false));
+    }
+    let aim_angle = game.serve_aim_angle;
+    assert!(aim_angle != 0.0 && aim_angle.abs() <= GameState::MAX_SERVE_ANGLE);
+
+    game.tick(31.0 * GameState::FIXED_STEP_SEC, key_state(false, false, true));
+
+    let ball = &game.balls[0];
+    assert!(!ball.is_stuck());
+    let expected = game.serve_vector(ball.movement_vector.length());
+    assert!((ball.movement_vector.x - expected.x).abs() < 10e-3 && (ball.movement_vector.y - expected.y).abs() < 10e-3);
+    assert!(ball.movement_vector.x.signum() == aim_angle.signum());
+}
+
+#[test]
+fn catch_effect_holds_ball_on_paddle_until_fire() {
+    let mut game = running_game_with_ball(FPoint::new(80.0, 60.0), FVector2d::new(0.0, -100.0));
+    game.active_effects.push(ActiveEffect { kind: PowerUpKind::Catch, time_left_sec: 10.0 });
+
+    run_ticks(&mut game, 1, 50);
+
+    assert!(game.balls[0].is_stuck());
+    assert!((game.balls[0].position.center.x - 80.0).abs() < 10e-3);
+
+    game.tick(51.0 * GameState::FIXED_STEP_SEC, key_state(false, false, true));
+    run_ticks(&mut game, 52, 60);
+
+    assert!(!game.balls[0].is_stuck());
+    assert!(game.balls[0].movement_vector.y > 0.0);
+    assert!(game.balls[0].position.center.y > 45.0);
+}
+
+#[test]
+fn multiball_serves_a_caught_ball_as_a_spread() {
+    let mut game = running_game_with_ball(FPoint::new(80.0, 60.0), FVector2d::new(0.0, -100.0));
+    game.active_effects.push(ActiveEffect { kind: PowerUpKind::Catch, time_left_sec: 10.0 });
+    run_ticks(&mut game, 1, 50);
+    assert!(game.balls[0].is_stuck());
+
+    game.capsules.push(Capsule::new(&FPoint::new(75.0, 45.0), PowerUpKind::Multiball));
+    run_ticks(&mut game, 51, 90);
+
+    assert_eq!(game.balls.len(), 3);
+    assert!(game.balls.iter().all(|ball| !ball.is_stuck() && ball.movement_vector.y > 0.0));
+    assert!(game.balls[0].movement_vector.x < game.balls[1].movement_vector.x);
+    assert!(game.balls[1].movement_vector.x < game.balls[2].movement_vector.x);
+    assert!(game.balls[0].position.center.x < game.balls[2].position.center.x);
+}
+
+#[test]
+fn laser_fires_bolts_from_paddle_edges_with_cooldown() {
+    let mut game = running_game_with_ball(FPoint::new(300.0, 200.0), FVector2d::new(0.0, 10.0));
+    game.active_effects.push(ActiveEffect { kind: PowerUpKind::Laser, time_left_sec: 10.0 });
+
+    for tick in 1..=10 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, key_state(false, false, true));
+    }
+
+    assert_eq!(game.projectiles.len(), 2);
+    assert!(game.projectiles[0].position.center().x < game.paddle.position.center().x);
+    assert!(game.projectiles[1].position.center().x > game.paddle.position.center().x);
+    assert!(game.laser_cooldown_left_sec > 0.0);
+}
+
+#[test]
+fn laser_bolts_damage_bricks_like_ball_hits() {
+    let mut game = running_game_with_ball(FPoint::new(300.0, 200.0), FVector2d::new(0.0, 10.0));
+    game.set_bricks(vec![
+        Brick::new(40.0, 100.0, 80.0, 120.0, BrickVariety::standard(1)).with_hit_points(2),
+        Brick::new(90.0, 100.0, 130.0, 120.0, BrickVariety::standard(2)),
+    ]);
+    game.projectiles.push(Projectile::new(&FPoint::new(60.0, 90.0)));
+
+    run_ticks(&mut game, 1, 40);
+
+    assert!(game.projectiles.is_empty());
+    assert_eq!(game.bricks()[0].hit_points, 1);
+    assert_eq!(game.score, ScoreRules::default().standard_hit);
+}
+
+fn pointer_at(x: f32) -> KeyboardState {
+    KeyboardState { pointer_x: Some(x), ..KeyboardState::none() }
+}
+
+#[test]
+fn paddle_follows_pointer_with_capped_speed() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    let start_x = game.paddle.position.center().x;
+
+    game.tick(GameState::FIXED_STEP_SEC, pointer_at(start_x + 100.0));
+    let after_one_step = game.paddle.position.center().x;
+    for tick in 2..=40 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, pointer_at(start_x + 100.0));
+    }
+
+    assert!((after_one_step - start_x - 1000.0 * GameState::FIXED_STEP_SEC as f32).abs() < 0.01, "Actual: {}", after_one_step);
+    assert!((game.paddle.position.center().x - (start_x + 100.0)).abs() < 0.01);
+    assert!(game.paddle.vector.x.abs() < 0.01);
+}
+
+#[test]
+fn paddle_vector_matches_pointer_movement() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    let start_x = game.paddle.position.center().x;
+
+    game.tick(GameState::FIXED_STEP_SEC, pointer_at(start_x + 1.0));
+
+    assert!((game.paddle.position.center().x - (start_x + 1.0)).abs() < 0.001);
+    assert!((game.paddle.vector.x - 1.0 / GameState::FIXED_STEP_SEC as f32).abs() < 0.1, "Actual: {:?}", game.paddle.vector);
+}
+
+#[test]
+fn paddle_held_at_a_wall_has_no_velocity() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    let beyond_right_wall = game.field.right() + 100.0;
+
+    for tick in 1..=100 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, pointer_at(beyond_right_wall));
+    }
+
+    assert_eq!(game.paddle.position.right(), game.field.right());
+    assert_eq!(game.paddle.vector.x, 0.0);
+
+    game.tick(101.0 * GameState::FIXED_STEP_SEC, key_state(true, false, false));
+
+    assert!((game.paddle.vector.x + 200.0).abs() < 0.1, "Actual: {:?}", game.paddle.vector);
+}
+
+#[test]
+fn analog_axis_scales_paddle_speed() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    let start_x = game.paddle.position.center().x;
+
+    game.tick(0.1, KeyboardState { analog_x: 0.5, ..KeyboardState::none() });
+
+    assert!((game.paddle.position.center().x - (start_x + 10.0)).abs() < 0.01, "Actual: {}", game.paddle.position.center().x);
+}
+
+fn fire() -> KeyboardState {
+    KeyboardState { fire: true, ..KeyboardState::none() }
+}
+
+fn pause() -> KeyboardState {
+    KeyboardState { pause: true, ..KeyboardState::none() }
+}
+
+#[test]
+fn title_screen_waits_for_fire_without_serving() {
+    let mut game = GameState::make_initial().with_title_screen();
+
+    run_ticks(&mut game, 1, 10);
+    assert_eq!(game.time_state, TimeState::Title);
+
+    game.tick(11.0 * GameState::FIXED_STEP_SEC, fire());
+    assert_eq!(game.time_state, TimeState::Stopped);
+    game.tick(12.0 * GameState::FIXED_STEP_SEC, fire());
+    run_ticks(&mut game, 13, 20);
+
+    assert!(matches!(game.time_state, TimeState::Running { .. }));
+    assert!(game.balls[0].is_stuck());
+}
+
+#[test]
+fn paused_time_is_not_simulated_after_resuming() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 200.0), FVector2d::new(0.0, 100.0));
+    game.tick(GameState::FIXED_STEP_SEC, pause());
+    let paused_y = game.balls[0].position.center.y;
+
+    game.tick(1.0, KeyboardState::none());
+    game.tick(5.0, pause());
+    game.tick(5.0 + GameState::FIXED_STEP_SEC, KeyboardState::none());
+    let resumed_y = game.balls[0].position.center.y;
+    game.tick(5.0 + 2.0 * GameState::FIXED_STEP_SEC, KeyboardState::none());
+
+    assert!(matches!(game.time_state, TimeState::Running { .. }));
+    assert_eq!(resumed_y, paused_y);
+    assert!((game.balls[0].position.center.y - paused_y - 100.0 * GameState::FIXED_STEP_SEC as f32).abs() < 10e-4);
+}
+
+#[test]
+fn lost_ball_waits_for_fire_before_the_next_serve() {
+    let mut game = running_game_with_ball(FPoint::new(200.0, 5.0), FVector2d::new(0.0, -2000.0));
+    game.lives_left = 2;
+
+    run_ticks(&mut game, 1, 10);
+    assert_eq!(game.time_state, TimeState::LifeLost);
+    assert_eq!(game.lives_left, 1);
+
+    game.tick(11.0 * GameState::FIXED_STEP_SEC, fire());
+    assert_eq!(game.time_state, TimeState::Stopped);
+}
+
+#[test]
+fn fire_after_game_over_restarts_on_the_same_levels() {
+    let mut game = GameState::with_levels(vec![single_brick_level("First"), single_brick_level("Second")]).unwrap();
+    game.score_rules.level_clear_bonus = 7;
+    let tick = clear_current_level(&mut game, 0);
+    game.tick((tick + 1) as f64 * GameState::FIXED_STEP_SEC, fire());
+    game.balls[0].stuck_offset = None;
+    game.balls[0].position.center = FPoint::new(200.0, 5.0);
+    game.balls[0].movement_vector = FVector2d::new(0.0, -2000.0);
+    game.lives_left = 1;
+    run_ticks(&mut game, tick + 2, tick + 10);
+    assert_eq!(game.time_state, TimeState::GameOver);
+
+    game.tick((tick + 11) as f64 * GameState::FIXED_STEP_SEC, fire());
+
+    assert_eq!(game.time_state, TimeState::Stopped);
+    assert_eq!(game.current_level, 0);
+    assert_eq!(game.levels.len(), 2);
+    assert_eq!(game.lives_left, 3);
+    assert_eq!(game.score, 0);
+    assert_eq!(game.score_rules.level_clear_bonus, 7);
+}
+
+#[test]
+fn fire_after_victory_returns_to_title() {
+    let mut game = GameState::with_levels(vec![single_brick_level("Only")]).unwrap();
+    let tick = clear_current_level(&mut game, 0);
+
+    game.tick((tick + 1) as f64 * GameState::FIXED_STEP_SEC, fire());
+
+    assert_eq!(game.time_state, TimeState::Title);
+    assert!(!game.bricks().is_empty());
+}
+
+#[test]
+fn speed_rules_clamp_speed_and_steepen_flat_paths() {
+    let rules = SpeedRules::default();
+
+    assert!((rules.limit(&FVector2d::new(0.0, 1000.0)).length() - rules.max_speed).abs() < 10e-3);
+    assert!((rules.limit(&FVector2d::new(0.0, -10.0)).length() - rules.min_speed).abs() < 10e-3);
+
+    let steepened = rules.limit(&FVector2d::new(-150.0, 1.0));
+    assert!((steepened.length() - 150.0).abs() < 10e-3);
+    assert!(steepened.x < 0.0 && steepened.y > 0.0);
+    assert!((steepened.y / steepened.length() - rules.min_vertical_angle.sin()).abs() < 10e-6);
+
+    let steep_enough = FVector2d::new(100.0, -100.0);
+    let kept = rules.limit(&steep_enough);
+    assert!((kept.x - steep_enough.x).abs() < 10e-4 && (kept.y - steep_enough.y).abs() < 10e-4);
+}
+
+fn speed_after_paddle_bounce(paddle_hits_per_speed_up: u32) -> f32 {
+    let mut game = running_game_with_ball(FPoint::new(75.0, 60.0), FVector2d::new(0.0, -100.0));
+    game.speed_rules.paddle_hits_per_speed_up = paddle_hits_per_speed_up;
+    game.speed_rules.speed_up_percent = 10.0;
+    run_ticks(&mut game, 1, 60);
+    assert_eq!(game.paddle_hits, 1);
+    assert!(game.balls[0].movement_vector.y > 0.0);
+    game.balls[0].movement_vector.length()
+}
+
+#[test]
+fn ball_speeds_up_every_k_paddle_hits() {
+    assert!((speed_after_paddle_bounce(1) - 110.0).abs() < 10e-3);
+    assert!((speed_after_paddle_bounce(2) - 100.0).abs() < 10e-3);
+    assert!((speed_after_paddle_bounce(0) - 100.0).abs() < 10e-3);
+}
+
+#[test]
+fn top_wall_speeds_up_ball_when_enabled() {
+    let mut game = running_game_with_ball(FPoint::new(300.0, 440.0), FVector2d::new(0.0, 100.0));
+    game.speed_rules.speed_up_on_top_wall = true;
+
+    run_ticks(&mut game, 1, 60);
+
+    assert!(game.balls[0].movement_vector.y < 0.0);
+    assert!((game.balls[0].movement_vector.length() - 105.0).abs() < 10e-3);
+}
+
+#[test]
+fn paddle_bounce_model_sets_angle_from_hit_position() {
+    let mut game = running_game_with_ball(FPoint::new(90.0, 60.0), FVector2d::new(0.0, -100.0));
+    game.paddle_bounce = PaddleBounce::HitPosition { max_angle: std::f32::consts::FRAC_PI_3 };
+    let hit_offset = (90.0 - game.paddle.position.center().x) / (game.paddle.position.width() / 2.0);
+
+    run_ticks(&mut game, 1, 60);
+
+    let outgoing = &game.balls[0].movement_vector;
+    assert!((outgoing.x.atan2(outgoing.y) - hit_offset * std::f32::consts::FRAC_PI_3).abs() < 10e-4, "Actual: {:?}", outgoing);
+}
+
+fn bricks_with_seam_at_50() -> Vec<Brick> {
+    vec![
+        Brick::new(10.0, 200.0, 50.0, 220.0, BrickVariety::standard(1)),
+        Brick::new(50.0, 200.0, 90.0, 220.0, BrickVariety::standard(1)),
+        steel_brick_at(300.0, 400.0, 340.0, 410.0),
+    ]
+}
+
+#[test]
+fn ball_hitting_a_seam_destroys_both_bricks() {
+    let mut game = running_game_with_ball(FPoint::new(50.0, 180.0), FVector2d::new(0.0, 100.0));
+    game.set_bricks(bricks_with_seam_at_50());
+
+    run_ticks(&mut game, 1, 60);
+
+    assert_eq!(game.bricks().len(), 1);
+    assert_eq!(game.balls[0].movement_vector.x, 0.0);
+    assert!(game.balls[0].movement_vector.y < 0.0);
+}
+
+#[test]
+fn ball_next_to_a_seam_breaks_only_the_brick_it_touches() {
+    // Reaches the bottom face of the right brick just before the corner of the left one
+    let mut game = running_game_with_ball(FPoint::new(51.63, 180.2), FVector2d::new(-10.0, 100.0));
+    game.set_bricks(bricks_with_seam_at_50());
+
+    run_ticks(&mut game, 1, 60);
+
+    assert_eq!(game.bricks().len(), 2);
+    assert_eq!(game.bricks()[0].position.right(), 50.0);
+    let outgoing = &game.balls[0].movement_vector;
+    assert!((outgoing.x + 10.0).abs() < 10e-4 && (outgoing.y + 100.0).abs() < 10e-4, "Actual: {:?}", outgoing);
+}
+
+#[test]
+fn flat_face_wins_over_a_corner_touched_at_the_same_time() {
+    // Touches the bottom face of the left brick and the corner of the right
+    // one, which sits one unit lower, both exactly at the start of the step
+    let mut game = running_game_with_ball(FPoint::new(100.0, 195.0), FVector2d::new(0.0, 100.0));
+    game.balls[0].position.radius = 5.0;
+    game.set_bricks(vec![
+        Brick::new(60.0, 200.0, 103.0, 220.0, BrickVariety::standard(1)),
+        Brick::new(103.0, 199.0, 143.0, 219.0, BrickVariety::standard(1)),
+        steel_brick_at(300.0, 400.0, 340.0, 410.0),
+    ]);
+
+    run_ticks(&mut game, 1, 1);
+
+    assert_eq!(game.bricks().len(), 1);
+    let outgoing = &game.balls[0].movement_vector;
+    assert!(outgoing.x == 0.0 && outgoing.y == -100.0, "Actual: {:?}", outgoing);
+}
+
+fn ball_in_corner_of_paddle_and_brick(paddle_input: KeyboardState) -> GameState {
+    let mut game = running_game_with_ball(FPoint::new(0.0, 0.0), FVector2d::new(100.0, -100.0));
+    game.paddle_bounce = PaddleBounce::VelocityTransfer { transfer: 1.0 };
+    let paddle = game.paddle.position.clone();
+    let brick_left = paddle.right() + 2.0;
+    game.set_bricks(vec![
+        Brick::new(brick_left, paddle.bottom(), brick_left + 40.0, paddle.top() + 20.0, BrickVariety::standard(1)),
+        steel_brick_at(300.0, 400.0, 340.0, 410.0),
+    ]);
+    game.balls[0].position.center = FPoint::new(brick_left - 4.0, paddle.top() + 4.0);
+    game.tick(GameState::FIXED_STEP_SEC, paddle_input);
+    game
+}
+
+#[test]
+fn paddle_and_brick_hit_in_one_step_send_the_ball_up_and_away() {
+    for paddle_input in [KeyboardState::none(), key_state(true, false, false), key_state(false, true, false)] {
+        let game = ball_in_corner_of_paddle_and_brick(paddle_input.clone());
+
+        let outgoing = &game.balls[0].movement_vector;
+        assert!(outgoing.x < 0.0 && outgoing.y > 0.0, "Input: {:?}. Actual: {:?}", paddle_input, outgoing);
+        assert_eq!(game.bricks().len(), 1);
+    }
+}
+
+#[test]
+fn ball_reflects_both_components_in_a_field_corner() {
+    let mut game = running_game_with_ball(FPoint::new(0.0, 0.0), FVector2d::new(-100.0, 100.0));
+    game.balls[0].position.center = FPoint::new(game.field.left() + 24.0, game.field.top() - 24.0);
+
+    run_ticks(&mut game, 1, 40);
+
+    let outgoing = &game.balls[0].movement_vector;
+    assert!((outgoing.x - 100.0).abs() < 10e-4 && (outgoing.y + 100.0).abs() < 10e-4, "Actual: {:?}", outgoing);
+}
+
+#[test]
+fn paddle_pushes_an_embedded_ball_out_along_the_normal() {
+    let paddle = GameState::make_initial().paddle.position;
+    let tests = [
+        ("Near the right face", FPoint::new(paddle.right() - 1.0, paddle.top() - 2.0), FVector2d::new(20.0, 100.0), FPoint::new(paddle.right() + 4.0, paddle.top() - 2.0)),
+        ("Near the bottom face", FPoint::new(paddle.center().x, paddle.bottom() + 1.0), FVector2d::new(20.0, -100.0), FPoint::new(paddle.center().x, paddle.bottom() - 4.0)),
+    ];
+
+    for (test_name, center, vector, expected_center) in tests.iter() {
+        let mut game = running_game_with_ball(center.clone(), vector.clone());
+
+        run_ticks(&mut game, 1, 1);
+
+        let ball = &game.balls[0];
+        let moved = vector.clone().mul_scalar(GameState::FIXED_STEP_SEC as f32);
+        assert!(ball.position.center.is_same(&expected_center.add(moved), 10e-4), "Test failed {:?}. Actual: {:?}", test_name, ball);
+        assert!(ball.movement_vector.x == vector.x && ball.movement_vector.y == vector.y, "Actual: {:?}", ball.movement_vector);
+    }
+}
+
+#[test]
+fn ball_squeezed_between_paddle_and_wall_leaves_over_the_top() {
+    let mut game = running_game_with_ball(FPoint::new(0.0, 0.0), FVector2d::new(0.0, -100.0));
+    let paddle = game.paddle.position.clone();
+    game.paddle.position = paddle.with_right_at(game.field.right() - 3.0);
+    game.balls[0].position.center = FPoint::new(game.field.right() - 4.0, paddle.top() - 3.0);
+
+    for tick in 1..=10 {
+        game.tick(tick as f64 * GameState::FIXED_STEP_SEC, key_state(false, true, false));
+    }
+
+    let ball = &game.balls[0];
+    assert!(circle_rectangle_collision(&ball.position, &game.paddle.position).is_none_or(|collision| collision.depth < 10e-4), "Actual: {:?}", ball);
+    assert!(ball.position.center.y > game.paddle.position.top());
+    assert!(ball.position.center.x <= game.field.right() - ball.position.radius + 10e-4, "Actual: {:?}", ball);
+    assert!(ball.movement_vector.y > 0.0, "Actual: {:?}", ball.movement_vector);
+}
+
+#[test]
+fn ball_pushed_out_of_a_brick_into_another_leaves_both() {
+    let mut game = running_game_with_ball(FPoint::new(192.0, 319.0), FVector2d::new(-20.0, 100.0));
+    game.set_bricks(vec![
+        Brick::new(100.0, 300.0, 200.0, 320.0, BrickVariety::standard(1)),
+        Brick::new(190.0, 320.0, 260.0, 360.0, BrickVariety::standard(1)),
+    ]);
+
+    run_ticks(&mut game, 1, 1);
+
+    let ball = &game.balls[0];
+    for brick in game.bricks() {
+        assert!(circle_rectangle_collision(&ball.position, &brick.position).is_none_or(|collision| collision.depth < 10e-4), "Actual: {:?}", ball);
+    }
+    assert_eq!(game.bricks().len(), 2);
+}
@@ -2,6 +2,7 @@ use std::f32::consts::PI;
 use std::fmt;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rectangle {
     pub x1: f32,
     pub y1: f32,
@@ -33,7 +34,6 @@ impl Rectangle {
     }
 
     pub fn with_left_at(&self, new_left: f32) -> Rectangle {
-        let old_left = self.left();
         Rectangle {
             x1: new_left,
             y1: self.y1,
@@ -43,7 +43,6 @@ impl Rectangle {
     }
 
     pub fn with_right_at(&self, new_right: f32) -> Rectangle {
-        let old_right = self.right();
         Rectangle {
             x1: new_right - f32::abs(self.x1 - self.x2),
             y1: self.y1,
@@ -93,6 +92,34 @@ impl Rectangle {
         FPoint::new(self.right(), self.bottom())
     }
 
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        self.left() <= other.right()
+            && self.right() >= other.left()
+            && self.bottom() <= other.top()
+            && self.top() >= other.bottom()
+    }
+
+    pub fn width(&self) -> f32 {
+        self.right() - self.left()
+    }
+
+    pub fn center(&self) -> FPoint {
+        FPoint::new((self.left() + self.right()) / 2.0, (self.bottom() + self.top()) / 2.0)
+    }
+
+    pub fn within(&self, other: &Rectangle) -> bool {
+        self.bottom_left().within_rectangle(other) && self.top_right().within_rectangle(other)
+    }
+
+    pub fn lerp(&self, other: &Rectangle, alpha: f32) -> Rectangle {
+        Rectangle {
+            x1: self.x1 + (other.x1 - self.x1) * alpha,
+            y1: self.y1 + (other.y1 - self.y1) * alpha,
+            x2: self.x2 + (other.x2 - self.x2) * alpha,
+            y2: self.y2 + (other.y2 - self.y2) * alpha,
+        }
+    }
+
     pub fn mutable_set(&mut self, rect: Rectangle) {
         self.x1 = rect.x1;
         self.y1 = rect.y1;
@@ -102,12 +129,14 @@ impl Rectangle {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ISize {
     pub h: i32,
     pub w: i32,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FPoint {
     pub x: f32,
     pub y: f32,
@@ -129,6 +158,13 @@ impl FPoint {
         }
     }
 
+    pub fn lerp(&self, other: &FPoint, alpha: f32) -> FPoint {
+        FPoint {
+            x: self.x + (other.x - self.x) * alpha,
+            y: self.y + (other.y - self.y) * alpha,
+        }
+    }
+
     pub fn within_rectangle(&self, rectangle: &Rectangle) -> bool {
         self.x >= rectangle.left()
             && self.x <= rectangle.right()
@@ -147,6 +183,7 @@ impl FPoint {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FVector2d {
     pub x: f32,
     pub y: f32,
@@ -180,25 +217,25 @@ impl FVector2d {
     pub fn invert(mut self) -> FVector2d {
         self.x = -self.x;
         self.y = -self.y;
-        return self;
+        self
     }
 
     pub fn plus(mut self, other: &FVector2d) -> FVector2d {
         self.x += other.x;
         self.y += other.y;
-        return self;
+        self
     }
 
     pub fn minus(mut self, other: &FVector2d) -> FVector2d {
         self.x -= other.x;
         self.y -= other.y;
-        return self;
+        self
     }
 
     pub fn mul_scalar(mut self, scalar: f32) -> FVector2d {
         self.x *= scalar;
         self.y *= scalar;
-        return self;
+        self
     }
 
     pub fn dot_product(&self, other: &FVector2d) -> f32 {
@@ -211,15 +248,14 @@ impl FVector2d {
             self.x /= length;
             self.y /= length;
         }
-        return self;
+        self
     }
 
     pub fn reflect(ray: &FVector2d, surface_normal: &FVector2d) -> FVector2d {
         let normalized_normal = surface_normal.clone().normalize();
         let dot = ray.dot_product(&normalized_normal);
-        let result = ray.clone()
-            .minus(&normalized_normal.mul_scalar(2.0 * dot));
-        return result;
+        ray.clone()
+            .minus(&normalized_normal.mul_scalar(2.0 * dot))
     }
 
     pub fn rotate_clockwise(mut self, clockwise_angle: f32) -> FVector2d {
@@ -228,7 +264,7 @@ impl FVector2d {
         let new_y = self.x * sn + self.y * cs;
         self.x = new_x;
         self.y = new_y;
-        return self;
+        self
     }
 
     pub fn is_zero(&self) -> bool {
@@ -244,6 +280,7 @@ impl FVector2d {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PolarVector {
     pub magnitude: f32,
     pub direction: f32
@@ -265,6 +302,7 @@ impl fmt::Debug for PolarVector {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Circle {
     pub center: FPoint,
     pub radius: f32
@@ -276,60 +314,314 @@ impl Circle {
     }
 }
 
-#[derive(Debug)]
+// Flat hits push along a face normal. Corner hits push away from a single
+// point, a rectangle corner or the closest point of another circle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CollisionKind {
+    Flat,
+    Corner,
+}
+
+// Normal points from the obstacle towards the circle. Depth is how far the
+// circle has to move along it to stop overlapping, 0 for a touching contact.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Collision {
     pub point: FPoint,
-    pub normal: FVector2d
+    pub normal: FVector2d,
+    pub kind: CollisionKind,
+    pub depth: f32,
+}
+
+impl Collision {
+    pub fn is_flat(&self) -> bool {
+        self.kind == CollisionKind::Flat
+    }
 }
 
+// Classified by the closest point of the rectangle, a circle with its center
+// inside is pushed out through the nearest face. The normal has unit length.
 pub fn circle_rectangle_collision(circle: &Circle, rectangle: &Rectangle) -> Option<Collision> {
-    let radius_sq = circle.radius * circle.radius;
-    let collistion_point_opt = if FPoint::new(circle.center.x + circle.radius, circle.center.y).within_rectangle(rectangle) {
-        Some(FPoint::new(
-            rectangle.left(),
-            circle.center.y,
-        ))
-    } else if FPoint::new(circle.center.x - circle.radius, circle.center.y).within_rectangle(rectangle) {
-        Some(FPoint::new(
-            rectangle.right(),
-            circle.center.y,
-        ))
-    } else if FPoint::new(circle.center.x, circle.center.y + circle.radius).within_rectangle(rectangle) {
-        Some(FPoint::new(
-            circle.center.x,
-            rectangle.bottom(),
-        ))
-    } else if FPoint::new(circle.center.x, circle.center.y - circle.radius).within_rectangle(rectangle) {
-        Some(FPoint::new(
-            circle.center.x,
-            rectangle.top(),
-        ))
-    } else if FPoint::sq_dist(&circle.center, &rectangle.bottom_left()) < radius_sq {
-        Some(rectangle.bottom_left())
-    } else if FPoint::sq_dist(&circle.center, &rectangle.bottom_right()) < radius_sq {
-        Some(rectangle.bottom_right())
-    } else if FPoint::sq_dist(&circle.center, &rectangle.top_left()) < radius_sq {
-        Some(rectangle.top_left())
-    } else if FPoint::sq_dist(&circle.center, &rectangle.top_right()) < radius_sq {
-        Some(rectangle.top_right())
+    let center = &circle.center;
+    let radius = circle.radius;
+    let closest = FPoint::new(
+        center.x.clamp(rectangle.left(), rectangle.right()),
+        center.y.clamp(rectangle.bottom(), rectangle.top()),
+    );
+    if closest.x == center.x && closest.y == center.y {
+        let faces = [
+            (center.x - rectangle.left(), FPoint::new(rectangle.left(), center.y), FVector2d::new(-1.0, 0.0)),
+            (rectangle.right() - center.x, FPoint::new(rectangle.right(), center.y), FVector2d::new(1.0, 0.0)),
+            (center.y - rectangle.bottom(), FPoint::new(center.x, rectangle.bottom()), FVector2d::new(0.0, -1.0)),
+            (rectangle.top() - center.y, FPoint::new(center.x, rectangle.top()), FVector2d::new(0.0, 1.0)),
+        ];
+        return faces.into_iter()
+            .min_by(|(first, ..), (second, ..)| first.total_cmp(second))
+            .map(|(distance, point, normal)| Collision { point, normal, kind: CollisionKind::Flat, depth: radius + distance });
+    }
+
+    let to_center = FVector2d::between(&closest, center);
+    if to_center.length_square() > radius * radius {
+        return None;
+    }
+    let kind = if closest.x == center.x || closest.y == center.y {
+        CollisionKind::Flat
     } else {
-        None
+        CollisionKind::Corner
     };
-
-    collistion_point_opt.map(|point| {
-        let normal = FVector2d::between(&point, &circle.center);
-        Collision { point, normal }
+    Some(Collision {
+        depth: radius - to_center.length(),
+        normal: to_center.normalize(),
+        point: closest,
+        kind,
     })
 }
 
-pub fn new_vector_after_circle_collision(circle_movement_vector: &FVector2d, collision: &Collision, other_movement_vector: &FVector2d) -> FVector2d {
+// A circle already moving away keeps its vector, overlaps are left to push_out_of_collision
+pub fn new_vector_after_circle_collision(circle_movement_vector: &FVector2d, collision: &Collision) -> FVector2d {
     let normalized_normal = collision.normal.clone().normalize();
     let normal_movement_dot_product = circle_movement_vector.dot_product(&normalized_normal);
     if normal_movement_dot_product >= 0.0 {
         circle_movement_vector.clone()
-            .plus(other_movement_vector)
     } else {
         circle_movement_vector.clone()
             .minus(&normalized_normal.mul_scalar(2.0 * normal_movement_dot_product))
     }
 }
+
+// Moves the circle along the normal by the penetration depth, so it only touches the obstacle
+pub fn push_out_of_collision(circle: &Circle, collision: &Collision) -> Circle {
+    if collision.depth <= 0.0 {
+        return circle.clone();
+    }
+    let correction = collision.normal.clone().normalize().mul_scalar(collision.depth);
+    Circle::new(circle.center.add(correction), circle.radius)
+}
+
+// Normal points from the second circle towards the first one
+pub fn circle_circle_collision(first: &Circle, second: &Circle) -> Option<Collision> {
+    let normal = FVector2d::between(&second.center, &first.center);
+    let radius_sum = first.radius + second.radius;
+    if normal.is_zero() || normal.length_square() >= radius_sum * radius_sum {
+        return None;
+    }
+    let point = second.center.add(normal.clone().normalize().mul_scalar(second.radius));
+    let depth = radius_sum - normal.length();
+    Some(Collision { point, normal, kind: CollisionKind::Corner, depth })
+}
+
+// Equal mass elastic bounce: the circles exchange their velocity components along the normal
+pub fn new_vectors_after_circles_collision(first_vector: &FVector2d, second_vector: &FVector2d, collision: &Collision) -> Option<(FVector2d, FVector2d)> {
+    let normalized_normal = collision.normal.clone().normalize();
+    let approach_speed = first_vector.clone().minus(second_vector).dot_product(&normalized_normal);
+    if approach_speed >= 0.0 {
+        return None;
+    }
+    let exchange = normalized_normal.mul_scalar(approach_speed);
+    Some((
+        first_vector.clone().minus(&exchange),
+        second_vector.clone().plus(&exchange),
+    ))
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SweptCollision {
+    pub time: f32,
+    pub collision: Collision
+}
+
+// Time of impact is a fraction of the movement vector, in [0, 1].
+// Only contacts the circle is moving towards are reported.
+pub fn swept_circle_rectangle_collision(circle: &Circle, movement: &FVector2d, rectangle: &Rectangle) -> Option<SweptCollision> {
+    if movement.is_zero() {
+        return None;
+    }
+
+    if let Some(collision) = circle_rectangle_collision(circle, rectangle) {
+        if movement.dot_product(&collision.normal) < 0.0 {
+            return Some(SweptCollision { time: 0.0, collision });
+        }
+    }
+
+    let radius = circle.radius;
+    let start = &circle.center;
+    let mut earliest: Option<(f32, FPoint, CollisionKind)> = None;
+    let mut consider = |time: f32, point: FPoint, kind: CollisionKind| {
+        if (0.0..=1.0).contains(&time) && earliest.as_ref().is_none_or(|(earliest_time, ..)| time < *earliest_time) {
+            earliest = Some((time, point, kind));
+        }
+    };
+
+    if movement.x > 0.0 {
+        let time = (rectangle.left() - radius - start.x) / movement.x;
+        let y = start.y + movement.y * time;
+        if y >= rectangle.bottom() && y <= rectangle.top() {
+            consider(time, FPoint::new(rectangle.left(), y), CollisionKind::Flat);
+        }
+    }
+    if movement.x < 0.0 {
+        let time = (rectangle.right() + radius - start.x) / movement.x;
+        let y = start.y + movement.y * time;
+        if y >= rectangle.bottom() && y <= rectangle.top() {
+            consider(time, FPoint::new(rectangle.right(), y), CollisionKind::Flat);
+        }
+    }
+    if movement.y > 0.0 {
+        let time = (rectangle.bottom() - radius - start.y) / movement.y;
+        let x = start.x + movement.x * time;
+        if x >= rectangle.left() && x <= rectangle.right() {
+            consider(time, FPoint::new(x, rectangle.bottom()), CollisionKind::Flat);
+        }
+    }
+    if movement.y < 0.0 {
+        let time = (rectangle.top() + radius - start.y) / movement.y;
+        let x = start.x + movement.x * time;
+        if x >= rectangle.left() && x <= rectangle.right() {
+            consider(time, FPoint::new(x, rectangle.top()), CollisionKind::Flat);
+        }
+    }
+
+    for corner in [rectangle.bottom_left(), rectangle.bottom_right(), rectangle.top_left(), rectangle.top_right()] {
+        let to_start = FVector2d::between(&corner, start);
+        let a = movement.length_square();
+        let b = 2.0 * to_start.dot_product(movement);
+        let c = to_start.length_square() - radius * radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            continue;
+        }
+        let time = (-b - discriminant.sqrt()) / (2.0 * a);
+        let center_at_impact = FPoint::new(start.x + movement.x * time, start.y + movement.y * time);
+        let outside_horizontally = center_at_impact.x <= rectangle.left() || center_at_impact.x >= rectangle.right();
+        let outside_vertically = center_at_impact.y <= rectangle.bottom() || center_at_impact.y >= rectangle.top();
+        if outside_horizontally && outside_vertically {
+            consider(time, corner, CollisionKind::Corner);
+        }
+    }
+
+    earliest.map(|(time, point, kind)| {
+        let center_at_impact = start.add(movement.clone().mul_scalar(time));
+        let normal = FVector2d::between(&point, &center_at_impact).normalize();
+        SweptCollision {
+            time,
+            collision: Collision { point, normal, kind, depth: 0.0 },
+        }
+    })
+}
+
+// Every rectangle the moving circle hits on its way, in the order given
+pub fn swept_collisions<'a>(circle: &Circle, movement: &FVector2d, rectangles: impl Iterator<Item = (usize, &'a Rectangle)>) -> Vec<(usize, SweptCollision)> {
+    rectangles
+        .filter_map(|(index, rectangle)| swept_circle_rectangle_collision(circle, movement, rectangle).map(|collision| (index, collision)))
+        .collect()
+}
+
+// Uniform grid broad phase. Items are identified by the caller's index and
+// registered in every cell their rectangle touches; anything outside the
+// bounds goes to the nearest edge cell, so queries never miss an item.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    bounds: Rectangle,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    len: usize,
+}
+
+// Empty single cell grid, a placeholder until the real one is built
+impl Default for SpatialGrid {
+    fn default() -> SpatialGrid {
+        SpatialGrid::new(&Rectangle::make_by_coords(0.0, 0.0, 0.0, 0.0), 1.0)
+    }
+}
+
+impl SpatialGrid {
+    pub fn new(bounds: &Rectangle, cell_size: f32) -> SpatialGrid {
+        let columns = ((bounds.width() / cell_size).ceil() as usize).max(1);
+        let rows = (((bounds.top() - bounds.bottom()) / cell_size).ceil() as usize).max(1);
+        SpatialGrid {
+            bounds: bounds.clone(),
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            len: 0,
+        }
+    }
+
+    pub fn with_items<'a>(bounds: &Rectangle, cell_size: f32, items: impl Iterator<Item = &'a Rectangle>) -> SpatialGrid {
+        let mut grid = SpatialGrid::new(bounds, cell_size);
+        for (id, rectangle) in items.enumerate() {
+            grid.insert(id, rectangle);
+        }
+        grid
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn column_of(&self, x: f32) -> usize {
+        (((x - self.bounds.left()) / self.cell_size).floor().max(0.0) as usize).min(self.columns - 1)
+    }
+
+    fn row_of(&self, y: f32) -> usize {
+        (((y - self.bounds.bottom()) / self.cell_size).floor().max(0.0) as usize).min(self.rows - 1)
+    }
+
+    fn cell_indices(&self, area: &Rectangle) -> impl Iterator<Item = usize> {
+        let columns = self.column_of(area.left())..=self.column_of(area.right());
+        let rows = self.row_of(area.bottom())..=self.row_of(area.top());
+        let row_length = self.columns;
+        rows.flat_map(move |row| columns.clone().map(move |column| row * row_length + column))
+    }
+
+    pub fn insert(&mut self, id: usize, rectangle: &Rectangle) {
+        for cell in self.cell_indices(rectangle).collect::<Vec<_>>() {
+            self.cells[cell].push(id);
+        }
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, id: usize, rectangle: &Rectangle) {
+        for cell in self.cell_indices(rectangle).collect::<Vec<_>>() {
+            self.cells[cell].retain(|item| *item != id);
+        }
+        self.len -= 1;
+    }
+
+    // Keeps the grid in step with Vec::swap_remove: the item at index is
+    // dropped and the last item takes its index
+    pub fn swap_remove(&mut self, index: usize, rectangle: &Rectangle, last_rectangle: &Rectangle) {
+        let last = self.len - 1;
+        self.remove(index, rectangle);
+        if index != last {
+            self.remove(last, last_rectangle);
+            self.insert(index, last_rectangle);
+        }
+    }
+
+    // Ids of every item sharing a cell with the area, in ascending order
+    pub fn query(&self, area: &Rectangle) -> Vec<usize> {
+        let mut ids: Vec<usize> = self.cell_indices(area)
+            .flat_map(|cell| self.cells[cell].iter().copied())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+// Everything a circle can touch while it moves along the vector
+pub fn swept_circle_bounds(circle: &Circle, movement: &FVector2d) -> Rectangle {
+    let start = &circle.center;
+    Rectangle::make_by_coords(start.x, start.y, start.x + movement.x, start.y + movement.y)
+        .grow(circle.radius)
+}
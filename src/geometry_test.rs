@@ -8,24 +8,24 @@ fn circle_rectangle_collision_no_collision() {
     let circle = Circle::new(FPoint::new(100.0, 100.0), 10.0);
     let rect = Rectangle::make_by_coords(10.0, 10.0, 20.0, 20.0);
 
-    assert_eq!(circle_rectangle_collision(&circle, &rect).is_some(), false);
+    assert!(circle_rectangle_collision(&circle, &rect).is_none());
 }
 
 #[test]
 fn circle_rectangle_collision_flat_collisions() {
     let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 100.0);
-    let tests = vec!(
+    let tests = [
         ("Top collistion", Circle::new(FPoint::new(50.0, 110.0), 11.0), FPoint::new(50.0, 100.0)),
         ("Bottom collision", Circle::new(FPoint::new(50.0, -10.0), 11.0), FPoint::new(50.0, 0.0)),
         ("Left collision", Circle::new(FPoint::new(-10.0, 50.0), 11.0), FPoint::new(0.0, 50.0)),
         ("Right collision", Circle::new(FPoint::new(110.0, 50.0), 11.0), FPoint::new(100.0, 50.0)),
-    );
+    ];
 
     for (test_name, circle, expected_collision_point) in tests.iter() {
-        let result = circle_rectangle_collision(&circle, &rect);
+        let result = circle_rectangle_collision(circle, &rect);
 
         assert!(matches!(&result,
-                         Some(CollisionKind::Flat(point)) if point.is_same(expected_collision_point, EPSILON)),
+                         Some(Collision { kind: CollisionKind::Flat, point, .. }) if point.is_same(expected_collision_point, EPSILON)),
                 "Test failed {:?}. Expected: {:?}. Actual: {:?}", test_name, expected_collision_point, result);
     }
 }
@@ -33,18 +33,264 @@ fn circle_rectangle_collision_flat_collisions() {
 #[test]
 fn circle_rectangle_collision_corner_collisions() {
     let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 100.0);
-    let tests = vec!(
+    let tests = [
         ("TopLeft collistion", Circle::new(FPoint::new(-7.0, 107.0), 11.0), FPoint::new(0.0, 100.0)),
         ("TopRight collision", Circle::new(FPoint::new(107.0, 107.0), 11.0), FPoint::new(100.0, 100.0)),
         ("BottomLeft collision", Circle::new(FPoint::new(-7.0, -7.0), 11.0), FPoint::new(0.0, 0.0)),
         ("BottomRight collision", Circle::new(FPoint::new(107.0, -7.0), 11.0), FPoint::new(100.0, 0.0)),
-    );
+    ];
 
     for (test_name, circle, expected_collision_point) in tests.iter() {
-        let result = circle_rectangle_collision(&circle, &rect);
+        let result = circle_rectangle_collision(circle, &rect);
 
         assert!(matches!(&result,
-                         Some(CollisionKind::Corner(point)) if point.is_same(expected_collision_point, EPSILON)),
+                         Some(Collision { kind: CollisionKind::Corner, point, .. }) if point.is_same(expected_collision_point, EPSILON)),
                 "Test failed {:?}. Expected: {:?}. Actual: {:?}", test_name, expected_collision_point, result);
     }
 }
+
+#[test]
+fn circle_rectangle_collision_penetration_depth() {
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 100.0);
+
+    let flat = circle_rectangle_collision(&Circle::new(FPoint::new(50.0, 108.0), 10.0), &rect).unwrap();
+    let corner = circle_rectangle_collision(&Circle::new(FPoint::new(103.0, 104.0), 10.0), &rect).unwrap();
+
+    assert!((flat.depth - 2.0).abs() < EPSILON, "Actual: {:?}", flat);
+    assert!((corner.depth - 5.0).abs() < EPSILON, "Actual: {:?}", corner);
+    assert!(corner.normal.x > 0.0 && corner.normal.y > 0.0);
+}
+
+#[test]
+fn circle_with_center_inside_collides_with_the_nearest_face() {
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 20.0);
+
+    let result = circle_rectangle_collision(&Circle::new(FPoint::new(97.0, 8.0), 4.0), &rect);
+
+    assert!(matches!(&result,
+                     Some(Collision { kind: CollisionKind::Flat, point, normal, depth })
+                         if point.is_same(&FPoint::new(100.0, 8.0), EPSILON) && normal.x == 1.0 && (depth - 7.0).abs() < EPSILON),
+            "Actual: {:?}", result);
+}
+
+#[test]
+fn push_out_leaves_the_circle_touching_the_nearest_face() {
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 20.0);
+    let circle = Circle::new(FPoint::new(97.0, 8.0), 4.0);
+
+    let pushed_out = push_out_of_collision(&circle, &circle_rectangle_collision(&circle, &rect).unwrap());
+
+    assert!(pushed_out.center.is_same(&FPoint::new(104.0, 8.0), EPSILON), "Actual: {:?}", pushed_out);
+}
+
+#[test]
+fn circle_moving_away_keeps_its_vector() {
+    let collision = circle_rectangle_collision(&Circle::new(FPoint::new(50.0, 22.0), 4.0), &Rectangle::make_by_coords(0.0, 0.0, 100.0, 20.0)).unwrap();
+
+    let result = new_vector_after_circle_collision(&FVector2d::new(30.0, 10.0), &collision);
+
+    assert!(result.x == 30.0 && result.y == 10.0, "Actual: {:?}", result);
+}
+
+fn random_circle_near(random: &mut crate::random::Random, rect: &Rectangle) -> Circle {
+    let radius = 1.0 + random.next_f32() * 20.0;
+    let x = rect.left() - 25.0 + random.next_f32() * (rect.width() + 50.0);
+    let y = rect.bottom() - 25.0 + random.next_f32() * (rect.top() - rect.bottom() + 50.0);
+    Circle::new(FPoint::new(x, y), radius)
+}
+
+#[test]
+fn circle_rectangle_collision_properties_hold_for_random_circles() {
+    let mut random = crate::random::Random::new(11);
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 60.0, 30.0);
+    let corners = [rect.bottom_left(), rect.bottom_right(), rect.top_left(), rect.top_right()];
+    let mut checked = 0;
+
+    for _ in 0..2000 {
+        let circle = random_circle_near(&mut random, &rect);
+        let Some(collision) = circle_rectangle_collision(&circle, &rect) else {
+            continue;
+        };
+        let pushed_out = Circle::new(circle.center.add(collision.normal.clone().mul_scalar(collision.depth + 10e-3)), circle.radius);
+        assert!(circle_rectangle_collision(&pushed_out, &rect).is_none(), "Still overlapping: {:?} {:?}", circle, collision);
+        if circle.center.within_rectangle(&rect) {
+            continue;
+        }
+        checked += 1;
+
+        let to_center = FVector2d::between(&collision.point, &circle.center);
+        assert!(to_center.dot_product(&collision.normal) > 0.0, "Normal points into the rectangle: {:?} {:?}", circle, collision);
+        assert!((collision.normal.length() - 1.0).abs() < 10e-4, "Actual: {:?}", collision);
+        assert!(collision.depth > 0.0 && collision.depth <= circle.radius + EPSILON, "Actual: {:?} {:?}", circle, collision);
+        match collision.kind {
+            CollisionKind::Flat => assert!(collision.normal.x == 0.0 || collision.normal.y == 0.0, "Actual: {:?}", collision),
+            CollisionKind::Corner => assert!(corners.iter().any(|corner| corner.is_same(&collision.point, EPSILON)), "Actual: {:?}", collision),
+        }
+    }
+    assert!(checked > 100, "Only {} overlapping circles", checked);
+}
+
+#[test]
+fn swept_circle_rectangle_collision_properties_hold_for_random_movements() {
+    let mut random = crate::random::Random::new(13);
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 60.0, 30.0);
+    let mut checked = 0;
+
+    for _ in 0..2000 {
+        let circle = random_circle_near(&mut random, &rect);
+        if circle_rectangle_collision(&circle, &rect).is_some() {
+            continue;
+        }
+        let movement = FVector2d::new(random.next_f32() * 100.0 - 50.0, random.next_f32() * 100.0 - 50.0);
+        let Some(SweptCollision { time, collision }) = swept_circle_rectangle_collision(&circle, &movement, &rect) else {
+            continue;
+        };
+        checked += 1;
+
+        let center_at_impact = circle.center.add(movement.clone().mul_scalar(time));
+        let distance = FPoint::sq_dist(&center_at_impact, &collision.point).sqrt();
+        assert!((0.0..=1.0).contains(&time), "Actual: {}", time);
+        assert!((distance - circle.radius).abs() < 10e-3, "Not touching: {:?} {:?} {}", circle, collision, time);
+        assert!(movement.dot_product(&collision.normal) < 0.0, "Moving away: {:?} {:?}", movement, collision);
+        assert!(FVector2d::between(&collision.point, &center_at_impact).dot_product(&collision.normal) > 0.0);
+        assert_eq!(collision.depth, 0.0);
+    }
+    assert!(checked > 100, "Only {} hits", checked);
+}
+
+#[test]
+fn swept_circle_rectangle_collision_flat_time_of_impact() {
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 20.0);
+    let circle = Circle::new(FPoint::new(50.0, -50.0), 5.0);
+    let movement = FVector2d::new(0.0, 100.0);
+
+    let result = swept_circle_rectangle_collision(&circle, &movement, &rect);
+
+    assert!(matches!(&result,
+                     Some(SweptCollision { time, collision }) if (time - 0.45).abs() < EPSILON && collision.point.is_same(&FPoint::new(50.0, 0.0), EPSILON)),
+            "Actual: {:?}", result);
+}
+
+#[test]
+fn swept_circle_rectangle_collision_does_not_tunnel() {
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 20.0);
+    let circle = Circle::new(FPoint::new(50.0, -10.0), 4.0);
+    let movement = FVector2d::new(0.0, 1000.0);
+
+    assert!(circle_rectangle_collision(&Circle::new(FPoint::new(50.0, 990.0), 4.0), &rect).is_none());
+    assert!(swept_circle_rectangle_collision(&circle, &movement, &rect).is_some());
+}
+
+#[test]
+fn swept_circle_rectangle_collision_corner() {
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 100.0);
+    let circle = Circle::new(FPoint::new(-20.0, 120.0), 5.0);
+    let movement = FVector2d::new(40.0, -40.0);
+
+    let result = swept_circle_rectangle_collision(&circle, &movement, &rect);
+
+    assert!(matches!(&result,
+                     Some(SweptCollision { collision, .. }) if collision.point.is_same(&FPoint::new(0.0, 100.0), EPSILON)),
+            "Actual: {:?}", result);
+}
+
+#[test]
+fn swept_circle_rectangle_collision_moving_away() {
+    let rect = Rectangle::make_by_coords(0.0, 0.0, 100.0, 20.0);
+    let circle = Circle::new(FPoint::new(50.0, 24.0), 4.0);
+    let movement = FVector2d::new(10.0, 100.0);
+
+    assert!(swept_circle_rectangle_collision(&circle, &movement, &rect).is_none());
+}
+
+#[test]
+fn circle_circle_collision_normal_points_to_first_circle() {
+    let first = Circle::new(FPoint::new(10.0, 0.0), 6.0);
+    let second = Circle::new(FPoint::new(0.0, 0.0), 6.0);
+
+    let result = circle_circle_collision(&first, &second);
+
+    assert!(matches!(&result,
+                     Some(Collision { point, normal, .. }) if point.is_same(&FPoint::new(6.0, 0.0), EPSILON) && normal.x > 0.0),
+            "Actual: {:?}", result);
+    assert!(circle_circle_collision(&first, &Circle::new(FPoint::new(-3.0, 0.0), 6.0)).is_none());
+}
+
+#[test]
+fn circles_exchange_normal_velocity_when_approaching() {
+    let collision = circle_circle_collision(
+        &Circle::new(FPoint::new(0.0, 0.0), 4.0),
+        &Circle::new(FPoint::new(6.0, 0.0), 4.0)
+    ).unwrap();
+
+    let result = new_vectors_after_circles_collision(&FVector2d::new(10.0, 5.0), &FVector2d::new(-2.0, 0.0), &collision);
+
+    assert!(matches!(&result,
+                     Some((first, second)) if (first.x + 2.0).abs() < EPSILON && (first.y - 5.0).abs() < EPSILON && (second.x - 10.0).abs() < EPSILON),
+            "Actual: {:?}", result);
+    assert!(new_vectors_after_circles_collision(&FVector2d::new(-10.0, 0.0), &FVector2d::new(2.0, 0.0), &collision).is_none());
+}
+
+#[test]
+fn spatial_grid_query_returns_sorted_candidates_near_the_area() {
+    let bounds = Rectangle::make_by_coords(0.0, 0.0, 200.0, 100.0);
+    let rectangles = [
+        Rectangle::make_by_size(10.0, 10.0, 30.0, 10.0),
+        Rectangle::make_by_size(150.0, 70.0, 30.0, 10.0),
+        Rectangle::make_by_size(35.0, 15.0, 30.0, 10.0),
+        Rectangle::make_by_size(-50.0, -50.0, 10.0, 10.0),
+    ];
+    let grid = SpatialGrid::with_items(&bounds, 20.0, rectangles.iter());
+
+    assert_eq!(grid.query(&Rectangle::make_by_size(30.0, 12.0, 5.0, 5.0)), vec![0, 2]);
+    assert_eq!(grid.query(&Rectangle::make_by_size(160.0, 75.0, 1.0, 1.0)), vec![1]);
+    assert_eq!(grid.query(&Rectangle::make_by_size(-20.0, -20.0, 1.0, 1.0)), vec![0, 3]);
+    assert!(grid.query(&Rectangle::make_by_size(100.0, 40.0, 5.0, 5.0)).is_empty());
+}
+
+#[test]
+fn spatial_grid_swap_remove_follows_vec_swap_remove() {
+    let bounds = Rectangle::make_by_coords(0.0, 0.0, 200.0, 100.0);
+    let mut rectangles = vec![
+        Rectangle::make_by_size(10.0, 10.0, 30.0, 10.0),
+        Rectangle::make_by_size(80.0, 50.0, 30.0, 10.0),
+        Rectangle::make_by_size(150.0, 70.0, 30.0, 10.0),
+    ];
+    let mut grid = SpatialGrid::with_items(&bounds, 20.0, rectangles.iter());
+
+    grid.swap_remove(0, &rectangles[0], &rectangles[2]);
+    rectangles.swap_remove(0);
+
+    assert_eq!(grid.len(), 2);
+    assert!(grid.query(&Rectangle::make_by_size(20.0, 12.0, 5.0, 5.0)).is_empty());
+    assert_eq!(grid.query(&Rectangle::make_by_size(160.0, 75.0, 1.0, 1.0)), vec![0]);
+    assert_eq!(grid.query(&Rectangle::make_by_size(90.0, 55.0, 1.0, 1.0)), vec![1]);
+}
+
+#[test]
+fn swept_bounds_cover_start_and_end_of_movement() {
+    let bounds = swept_circle_bounds(&Circle::new(FPoint::new(10.0, 20.0), 2.0), &FVector2d::new(-5.0, 8.0));
+
+    assert_eq!((bounds.left(), bounds.right(), bounds.bottom(), bounds.top()), (3.0, 12.0, 18.0, 30.0));
+}
+
+#[test]
+fn spatial_grid_finds_the_same_contacts_as_a_full_scan() {
+    let mut random = crate::random::Random::new(7);
+    let bounds = Rectangle::make_by_coords(0.0, 0.0, 400.0, 400.0);
+    let rectangles: Vec<Rectangle> = (0..300)
+        .map(|_| Rectangle::make_by_size(random.next_f32() * 390.0, random.next_f32() * 390.0, 10.0 + random.next_f32() * 30.0, 10.0))
+        .collect();
+    let grid = SpatialGrid::with_items(&bounds, 40.0, rectangles.iter());
+
+    for _ in 0..500 {
+        let circle = Circle::new(FPoint::new(random.next_f32() * 400.0, random.next_f32() * 400.0), 4.0);
+        let movement = FVector2d::new(random.next_f32() * 60.0 - 30.0, random.next_f32() * 60.0 - 30.0);
+        let full_scan = swept_collisions(&circle, &movement, rectangles.iter().enumerate());
+        let candidates = grid.query(&swept_circle_bounds(&circle, &movement));
+        let broad_phase = swept_collisions(&circle, &movement, candidates.into_iter().map(|index| (index, &rectangles[index])));
+
+        let times = |contacts: Vec<(usize, SweptCollision)>| contacts.into_iter().map(|(index, hit)| (index, hit.time)).collect::<Vec<_>>();
+        assert_eq!(times(full_scan), times(broad_phase));
+    }
+}
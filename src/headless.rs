@@ -0,0 +1,104 @@
+use std::fmt;
+
+use crate::game_state::*;
+
+pub struct FixedStepClock {
+    pub step_sec: f64,
+    pub now_sec: f64,
+}
+
+impl FixedStepClock {
+    pub fn new(step_sec: f64) -> FixedStepClock {
+        FixedStepClock { step_sec, now_sec: 0.0 }
+    }
+
+    pub fn advance(&mut self) -> f64 {
+        self.now_sec += self.step_sec;
+        self.now_sec
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptStep {
+    pub ticks: u32,
+    pub keyboard_state: KeyboardState,
+}
+
+#[derive(Debug, Clone)]
+pub struct InputScript {
+    pub steps: Vec<ScriptStep>,
+}
+
+#[derive(Debug)]
+pub struct ScriptParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl InputScript {
+    // One step per line: "<ticks> [left] [right] [fire] [pause] [x=<pointer x>] [axis=<analog x>]",
+    // '#' starts a comment
+    pub fn parse(text: &str) -> Result<InputScript, ScriptParseError> {
+        let mut steps = Vec::new();
+        for (index, raw_line) in text.lines().enumerate() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let ticks_word = words.next().unwrap_or("");
+            let ticks = ticks_word.parse::<u32>().map_err(|_| ScriptParseError {
+                line: index + 1,
+                message: format!("expected tick count, found '{}'", ticks_word),
+            })?;
+            let mut keyboard_state = KeyboardState::none();
+            for word in words {
+                match word {
+                    "left" => keyboard_state.move_left = true,
+                    "right" => keyboard_state.move_right = true,
+                    "fire" => keyboard_state.fire = true,
+                    "pause" => keyboard_state.pause = true,
+                    _ => match word.split_once('=') {
+                        Some((field @ ("x" | "axis"), value_word)) => {
+                            let value = value_word.parse::<f32>().map_err(|_| ScriptParseError {
+                                line: index + 1,
+                                message: format!("expected a number, found '{}'", value_word),
+                            })?;
+                            if field == "x" {
+                                keyboard_state.pointer_x = Some(value);
+                            } else {
+                                keyboard_state.analog_x = value;
+                            }
+                        },
+                        _ => return Err(ScriptParseError {
+                            line: index + 1,
+                            message: format!("unknown key '{}'", word),
+                        }),
+                    },
+                }
+            }
+            steps.push(ScriptStep { ticks, keyboard_state });
+        }
+        Ok(InputScript { steps })
+    }
+}
+
+pub fn run(game: &mut GameState, clock: &mut FixedStepClock, script: &InputScript) -> u32 {
+    let mut ticks_done = 0;
+    for step in script.steps.iter() {
+        for _ in 0..step.ticks {
+            if game.time_state == TimeState::GameOver || game.time_state == TimeState::Victory {
+                return ticks_done;
+            }
+            game.tick(clock.advance(), step.keyboard_state.clone());
+            ticks_done += 1;
+        }
+    }
+    ticks_done
+}
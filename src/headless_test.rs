@@ -0,0 +1,43 @@
+use crate::game_state::*;
+use crate::headless::*;
+
+#[test]
+fn input_script_parse_steps_and_comments() {
+    let script = InputScript::parse("# serve\n1 fire\n\n20 left right # both\n5\n").unwrap();
+
+    assert_eq!(script.steps.len(), 3);
+    assert_eq!(script.steps[0].ticks, 1);
+    assert!(script.steps[0].keyboard_state.fire);
+    assert!(script.steps[1].keyboard_state.move_left && script.steps[1].keyboard_state.move_right);
+    assert!(!script.steps[2].keyboard_state.fire);
+}
+
+#[test]
+fn input_script_parse_reports_line() {
+    let result = InputScript::parse("1 fire\n2 jump\n");
+
+    assert!(matches!(result, Err(ScriptParseError { line: 2, .. })), "Actual: {:?}", result);
+}
+
+#[test]
+fn run_advances_clock_by_fixed_steps() {
+    let script = InputScript::parse("1 fire\n99\n").unwrap();
+    let mut clock = FixedStepClock::new(0.01);
+    let mut game = GameState::make_initial();
+
+    let ticks = run(&mut game, &mut clock, &script);
+
+    assert_eq!(ticks, 100);
+    assert!((clock.now_sec - 1.0).abs() < 10e-6);
+    assert!(matches!(game.time_state, TimeState::Running { .. }));
+}
+
+#[test]
+fn input_script_parse_pointer_and_axis() {
+    let script = InputScript::parse("3 x=120.5 fire\n2 axis=-0.25\n").unwrap();
+
+    assert_eq!(script.steps[0].keyboard_state.pointer_x, Some(120.5));
+    assert!(script.steps[0].keyboard_state.fire);
+    assert_eq!(script.steps[1].keyboard_state.analog_x, -0.25);
+    assert!(InputScript::parse("1 x=left\n").is_err());
+}
@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::game_state::*;
+use crate::level::words_with_columns;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+    Quit,
+    Debug,
+    QuickSave,
+    QuickLoad,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::Pause,
+        Action::Quit,
+        Action::Debug,
+        Action::QuickSave,
+        Action::QuickLoad,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Fire => "fire",
+            Action::Pause => "pause",
+            Action::Quit => "quit",
+            Action::Debug => "debug",
+            Action::QuickSave => "quick_save",
+            Action::QuickLoad => "quick_load",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|action| action.name() == name)
+    }
+}
+
+// Backend independent key names. They follow Allegro's key codes, with the
+// leading underscore of the digit keys dropped.
+pub const KEY_NAMES: [&str; 118] = [
+    "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M",
+    "N", "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
+    "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
+    "Pad0", "Pad1", "Pad2", "Pad3", "Pad4", "Pad5", "Pad6", "Pad7", "Pad8", "Pad9",
+    "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12",
+    "Escape", "Tilde", "Minus", "Equals", "Backspace", "Tab", "Openbrace", "Closebrace",
+    "Enter", "Semicolon", "Quote", "Backslash", "Backslash2", "Comma", "Fullstop", "Slash",
+    "Space", "Insert", "Delete", "Home", "End", "PgUp", "PgDn",
+    "Left", "Right", "Up", "Down",
+    "PadSlash", "PadAsterisk", "PadMinus", "PadPlus", "PadDelete", "PadEnter",
+    "PrintScreen", "Pause", "AbntC1", "Yen", "Kana", "Convert", "NoConvert", "At",
+    "Circumflex", "Colon2", "Kanji", "PadEquals", "Backquote", "Semicolon2", "Command",
+    "LShift", "RShift", "LCtrl", "RCtrl", "Alt", "AltGr", "LWin", "RWin", "Menu",
+    "ScrollLock", "NumLock", "CapsLock",
+];
+
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    pub bindings: Vec<(Action, Vec<String>)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BindingConflict {
+    pub key: String,
+    pub actions: Vec<Action>,
+}
+
+impl fmt::Display for BindingConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.actions.iter().map(|action| action.name()).collect();
+        write!(f, "key {} is bound to {}", self.key, names.join(", "))
+    }
+}
+
+#[derive(Debug)]
+pub struct KeyBindingsParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for KeyBindingsParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum KeyBindingsLoadError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { path: PathBuf, error: KeyBindingsParseError },
+}
+
+impl fmt::Display for KeyBindingsLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyBindingsLoadError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            KeyBindingsLoadError::Parse { path, error } => write!(f, "{}:{}:{}: {}", path.display(), error.line, error.column, error.message),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn keys_for(&self, action: Action) -> &[String] {
+        self.bindings.iter()
+            .find(|(bound_action, _)| *bound_action == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn actions_for(&self, key: &str) -> Vec<Action> {
+        self.bindings.iter()
+            .filter(|(_, keys)| keys.iter().any(|bound_key| bound_key == key))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    pub fn is_active(&self, action: Action, pressed_keys: &HashSet<String>) -> bool {
+        self.keys_for(action).iter().any(|key| pressed_keys.contains(key))
+    }
+
+    pub fn keyboard_state(&self, pressed_keys: &HashSet<String>) -> KeyboardState {
+        KeyboardState {
+            move_left: self.is_active(Action::MoveLeft, pressed_keys),
+            move_right: self.is_active(Action::MoveRight, pressed_keys),
+            fire: self.is_active(Action::Fire, pressed_keys),
+            pause: self.is_active(Action::Pause, pressed_keys),
+            ..KeyboardState::none()
+        }
+    }
+
+    // Every key bound to more than one action, in key name order
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        let mut keys: Vec<&String> = self.bindings.iter().flat_map(|(_, keys)| keys.iter()).collect();
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .map(|key| BindingConflict { key: key.clone(), actions: self.actions_for(key) })
+            .filter(|conflict| conflict.actions.len() > 1)
+            .collect()
+    }
+
+    fn bind(&mut self, action: Action, keys: Vec<String>) {
+        match self.bindings.iter_mut().find(|(bound_action, _)| *bound_action == action) {
+            Some((_, bound_keys)) => *bound_keys = keys,
+            None => self.bindings.push((action, keys)),
+        }
+    }
+
+    // One action per line: "<action> <key> [<key> ...]", '#' starts a comment.
+    // Actions that are not mentioned keep their default keys.
+    pub fn parse(text: &str) -> Result<KeyBindings, KeyBindingsParseError> {
+        let mut key_bindings = KeyBindings::default();
+        let mut seen_actions = HashSet::new();
+        for (index, raw_line) in text.lines().enumerate() {
+            let line = index + 1;
+            let without_comment = raw_line.split('#').next().unwrap_or("");
+            let mut words = words_with_columns(without_comment).into_iter();
+            let Some((action_column, action_name)) = words.next() else {
+                continue;
+            };
+            let error = |column: usize, message: String| KeyBindingsParseError { line, column, message };
+            let action = Action::from_name(action_name)
+                .ok_or_else(|| error(action_column, format!("unknown action '{}'", action_name)))?;
+            if !seen_actions.insert(action) {
+                return Err(error(action_column, format!("action '{}' is already bound", action_name)));
+            }
+            let mut keys = Vec::new();
+            for (key_column, key) in words {
+                if !KEY_NAMES.contains(&key) {
+                    return Err(error(key_column, format!("unknown key '{}'", key)));
+                }
+                if !keys.iter().any(|bound_key| bound_key == key) {
+                    keys.push(key.to_string());
+                }
+            }
+            if keys.is_empty() {
+                return Err(error(action_column, format!("expected at least one key for '{}'", action_name)));
+            }
+            key_bindings.bind(action, keys);
+        }
+        Ok(key_bindings)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        let keys = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        KeyBindings {
+            bindings: vec![
+                (Action::MoveLeft, keys(&["A", "Left"])),
+                (Action::MoveRight, keys(&["D", "Right"])),
+                (Action::Fire, keys(&["Space"])),
+                (Action::Pause, keys(&["P", "Pause"])),
+                (Action::Quit, keys(&["Escape"])),
+                (Action::Debug, keys(&["F1"])),
+                (Action::QuickSave, keys(&["F5"])),
+                (Action::QuickLoad, keys(&["F9"])),
+            ],
+        }
+    }
+}
+
+// Stick positions inside the deadzone read as zero, the rest is rescaled so the
+// output still covers the whole -1..1 range without a jump at the deadzone edge
+pub fn analog_axis_with_deadzone(position: f32, deadzone: f32) -> f32 {
+    let magnitude = position.abs().min(1.0);
+    if magnitude <= deadzone {
+        return 0.0;
+    }
+    position.signum() * (magnitude - deadzone) / (1.0 - deadzone)
+}
+
+pub fn load_key_bindings(path: &Path) -> Result<KeyBindings, KeyBindingsLoadError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| KeyBindingsLoadError::Io { path: path.to_path_buf(), error })?;
+    KeyBindings::parse(&text)
+        .map_err(|error| KeyBindingsLoadError::Parse { path: path.to_path_buf(), error })
+}
@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+
+use crate::input::*;
+
+fn pressed(keys: &[&str]) -> HashSet<String> {
+    keys.iter().map(|key| key.to_string()).collect()
+}
+
+#[test]
+fn default_bindings_have_no_conflicts() {
+    let key_bindings = KeyBindings::default();
+
+    assert!(key_bindings.conflicts().is_empty());
+    assert!(Action::ALL.iter().all(|action| !key_bindings.keys_for(*action).is_empty()));
+}
+
+#[test]
+fn keyboard_state_follows_any_bound_key() {
+    let key_bindings = KeyBindings::default();
+
+    let keyboard_state = key_bindings.keyboard_state(&pressed(&["Left", "Space"]));
+
+    assert!(keyboard_state.move_left);
+    assert!(!keyboard_state.move_right);
+    assert!(keyboard_state.fire);
+}
+
+#[test]
+fn config_replaces_only_mentioned_actions() {
+    let key_bindings = KeyBindings::parse("# arrows only\nmove_left Left\nmove_right Right  # comment\nfire Up Space\n").unwrap();
+
+    assert_eq!(key_bindings.keys_for(Action::MoveLeft), ["Left"]);
+    assert_eq!(key_bindings.keys_for(Action::Fire), ["Up", "Space"]);
+    assert_eq!(key_bindings.keys_for(Action::Quit), ["Escape"]);
+    assert!(!key_bindings.keyboard_state(&pressed(&["A"])).move_left);
+}
+
+#[test]
+fn conflicting_bindings_are_reported() {
+    let key_bindings = KeyBindings::parse("fire Space P\nquick_save Space\n").unwrap();
+
+    let conflicts = key_bindings.conflicts();
+
+    assert_eq!(conflicts, vec![
+        BindingConflict { key: "P".to_string(), actions: vec![Action::Fire, Action::Pause] },
+        BindingConflict { key: "Space".to_string(), actions: vec![Action::Fire, Action::QuickSave] },
+    ]);
+    assert_eq!(conflicts[1].to_string(), "key Space is bound to fire, quick_save");
+}
+
+#[test]
+fn config_errors_point_at_the_offending_word() {
+    let unknown_key = KeyBindings::parse("\nfire  Spacebar\n").unwrap_err();
+    assert_eq!((unknown_key.line, unknown_key.column), (2, 7));
+
+    let unknown_action = KeyBindings::parse("jump Up\n").unwrap_err();
+    assert_eq!((unknown_action.line, unknown_action.column), (1, 1));
+
+    let no_keys = KeyBindings::parse("quit\n").unwrap_err();
+    assert!(no_keys.message.contains("at least one key"));
+
+    let twice = KeyBindings::parse("quit Q\nquit Escape\n").unwrap_err();
+    assert_eq!(twice.line, 2);
+}
+
+#[test]
+fn sample_config_parses_without_conflicts() {
+    let key_bindings = KeyBindings::parse(include_str!("../data/keys-arrows.cfg")).unwrap();
+
+    assert!(key_bindings.conflicts().is_empty());
+    assert_eq!(key_bindings.keys_for(Action::Pause), ["P", "Pause"]);
+}
+
+#[test]
+fn deadzone_zeroes_small_positions_and_rescales_the_rest() {
+    assert_eq!(analog_axis_with_deadzone(0.1, 0.2), 0.0);
+    assert_eq!(analog_axis_with_deadzone(-0.2, 0.2), 0.0);
+    assert!((analog_axis_with_deadzone(0.6, 0.2) - 0.5).abs() < 1e-6);
+    assert_eq!(analog_axis_with_deadzone(-1.0, 0.2), -1.0);
+    assert_eq!(analog_axis_with_deadzone(1.5, 0.2), 1.0);
+}
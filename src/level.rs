@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::game_state::*;
+use crate::geometry::*;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Level {
+    pub name: String,
+    pub field: Rectangle,
+    pub bricks: Vec<Brick>,
+    pub speed_rules: SpeedRules,
+}
+
+impl Level {
+    pub fn new(name: &str, bricks: Vec<Brick>) -> Level {
+        Level {
+            name: name.to_string(),
+            field: Rectangle::make_by_coords(10.0, 10.0, 410.0, 460.0),
+            bricks,
+            speed_rules: SpeedRules::default(),
+        }
+    }
+
+    pub fn with_field(mut self, field: Rectangle) -> Level {
+        self.field = field;
+        self
+    }
+
+    pub fn with_speed_rules(mut self, speed_rules: SpeedRules) -> Level {
+        self.speed_rules = speed_rules;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct LevelParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl LevelParseError {
+    fn new(line: usize, column: usize, message: String) -> LevelParseError {
+        LevelParseError { line, column, message }
+    }
+}
+
+impl fmt::Display for LevelParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelLoadError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { path: PathBuf, error: LevelParseError },
+    NoLevels { path: PathBuf },
+}
+
+impl fmt::Display for LevelLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelLoadError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            LevelLoadError::Parse { path, error } => write!(f, "{}:{}:{}: {}", path.display(), error.line, error.column, error.message),
+            LevelLoadError::NoLevels { path } => write!(f, "{}: no .level files found", path.display()),
+        }
+    }
+}
+
+// 1-based column of every whitespace separated word in the line
+pub(crate) fn words_with_columns(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut word_start: Option<usize> = None;
+    for (index, character) in line.char_indices() {
+        match (character.is_whitespace(), word_start) {
+            (true, Some(start)) => {
+                words.push((start + 1, &line[start..index]));
+                word_start = None;
+            },
+            (false, None) => word_start = Some(index),
+            _ => (),
+        }
+    }
+    if let Some(start) = word_start {
+        words.push((start + 1, &line[start..]));
+    }
+    words
+}
+
+fn parse_numbers<const N: usize>(line: usize, key_column: usize, words: &[(usize, &str)]) -> Result<[f32; N], LevelParseError> {
+    if words.len() != N {
+        return Err(LevelParseError::new(line, key_column, format!("expected {} numbers, found {}", N, words.len())));
+    }
+    let mut numbers = [0.0; N];
+    for (number, (column, word)) in numbers.iter_mut().zip(words.iter()) {
+        *number = word.parse::<f32>().map_err(|_| {
+            LevelParseError::new(line, *column, format!("expected a number, found '{}'", word))
+        })?;
+    }
+    Ok(numbers)
+}
+
+#[derive(Debug, Clone)]
+struct BrickTemplate {
+    variety: BrickVariety,
+    hit_points: i32,
+}
+
+fn parse_brick_template(line: usize, key_column: usize, words: &[(usize, &str)]) -> Result<(char, BrickTemplate), LevelParseError> {
+    let (symbol_column, symbol_word) = words.first()
+        .ok_or_else(|| LevelParseError::new(line, key_column, "expected a brick symbol".to_string()))?;
+    let mut symbol_chars = symbol_word.chars();
+    let symbol = match (symbol_chars.next(), symbol_chars.next()) {
+        (Some(symbol), None) if symbol != '.' => symbol,
+        _ => return Err(LevelParseError::new(line, *symbol_column, format!("brick symbol must be a single character other than '.', found '{}'", symbol_word))),
+    };
+    let parse_int = |(column, word): &(usize, &str)| word.parse::<i32>().map_err(|_| {
+        LevelParseError::new(line, *column, format!("expected an integer, found '{}'", word))
+    });
+    match words.get(1) {
+        Some((_, "steel")) if words.len() == 2 => Ok((symbol, BrickTemplate { variety: BrickVariety::Steel, hit_points: 1 })),
+        Some((_, "standard")) if words.len() == 3 || words.len() == 4 => {
+            let color = parse_int(&words[2])?;
+            let hit_points = match words.get(3) {
+                Some(word) => parse_int(word)?,
+                None => 1,
+            };
+            if hit_points < 1 {
+                return Err(LevelParseError::new(line, words[3].0, "hit points must be positive".to_string()));
+            }
+            Ok((symbol, BrickTemplate { variety: BrickVariety::standard(color), hit_points }))
+        },
+        Some((column, _)) => Err(LevelParseError::new(line, *column, "expected 'steel' or 'standard <color> [hit points]'".to_string())),
+        None => Err(LevelParseError::new(line, *symbol_column, "expected a brick variety".to_string())),
+    }
+}
+
+// Keeps the brick grid of a level to a sane number of cells
+const MAX_FIELD_SIDE: f32 = 10_000.0;
+
+// Header lines are "<key> <values>", '#' starts a comment. Everything after
+// the "grid" line is the brick layout, one character per cell, '.' for empty.
+pub fn parse_level(text: &str) -> Result<Level, LevelParseError> {
+    let mut name: Option<String> = None;
+    let mut field: Option<Rectangle> = None;
+    let mut cell_size: Option<[f32; 2]> = None;
+    let mut top_left: Option<[f32; 2]> = None;
+    let mut templates: HashMap<char, BrickTemplate> = HashMap::new();
+    let mut speed_rules = SpeedRules::default();
+    let mut grid_start: Option<usize> = None;
+    let lines: Vec<&str> = text.lines().collect();
+
+    for (index, raw_line) in lines.iter().enumerate() {
+        let line = index + 1;
+        let without_comment = raw_line.split('#').next().unwrap_or("");
+        let words = words_with_columns(without_comment);
+        let Some(&(key_column, key)) = words.first() else {
+            continue;
+        };
+        let values = &words[1..];
+        match key {
+            "name" => {
+                let (value_column, _) = values.first()
+                    .ok_or_else(|| LevelParseError::new(line, key_column, "expected a level name".to_string()))?;
+                name = Some(without_comment[value_column - 1..].trim().to_string());
+            },
+            "field" => {
+                let [x1, y1, x2, y2] = parse_numbers::<4>(line, key_column, values)?;
+                let sides_valid = [x2 - x1, y2 - y1].iter().all(|side| 0.0 < *side && *side <= MAX_FIELD_SIDE);
+                if !sides_valid || !x1.is_finite() || !y1.is_finite() {
+                    return Err(LevelParseError::new(line, key_column, format!("expected x1 < x2 and y1 < y2 with sides up to {}", MAX_FIELD_SIDE)));
+                }
+                field = Some(Rectangle::make_by_coords(x1, y1, x2, y2));
+            },
+            "speed" => {
+                let [serve_speed, min_speed, max_speed] = parse_numbers::<3>(line, key_column, values)?;
+                if !(0.0 < min_speed && min_speed <= serve_speed && serve_speed <= max_speed) {
+                    return Err(LevelParseError::new(line, key_column, "expected 0 < min <= serve <= max".to_string()));
+                }
+                speed_rules.serve_speed = serve_speed;
+                speed_rules.min_speed = min_speed;
+                speed_rules.max_speed = max_speed;
+            },
+            "speed_up" => {
+                let [percent, paddle_hits] = parse_numbers::<2>(line, key_column, values)?;
+                if percent < 0.0 || paddle_hits < 0.0 || paddle_hits.fract() != 0.0 {
+                    return Err(LevelParseError::new(line, key_column, "expected a non-negative percentage and paddle hit count".to_string()));
+                }
+                speed_rules.speed_up_percent = percent;
+                speed_rules.paddle_hits_per_speed_up = paddle_hits as u32;
+            },
+            "speed_up_top_wall" => match values.first() {
+                Some((value_column, _)) => return Err(LevelParseError::new(line, *value_column, "'speed_up_top_wall' takes no value".to_string())),
+                None => speed_rules.speed_up_on_top_wall = true,
+            },
+            "cell" => {
+                let [cell_width, cell_height] = parse_numbers::<2>(line, key_column, values)?;
+                if !(0.0 < cell_width && 0.0 < cell_height && cell_width.is_finite() && cell_height.is_finite()) {
+                    return Err(LevelParseError::new(line, key_column, "cell sizes must be positive".to_string()));
+                }
+                cell_size = Some([cell_width, cell_height]);
+            },
+            "top_left" => top_left = Some(parse_numbers::<2>(line, key_column, values)?),
+            "brick" => {
+                let (symbol, template) = parse_brick_template(line, key_column, values)?;
+                if templates.insert(symbol, template).is_some() {
+                    return Err(LevelParseError::new(line, values[0].0, format!("brick '{}' is already defined", symbol)));
+                }
+            },
+            "grid" => {
+                grid_start = Some(index + 1);
+                break;
+            },
+            _ => return Err(LevelParseError::new(line, key_column, format!("unknown key '{}'", key))),
+        }
+    }
+
+    let last_line = lines.len().max(1);
+    let missing = |what: &str| LevelParseError::new(last_line, 1, format!("missing '{}'", what));
+    let grid_start = grid_start.ok_or_else(|| missing("grid"))?;
+    let field = field.ok_or_else(|| missing("field"))?;
+    let [cell_width, cell_height] = cell_size.ok_or_else(|| missing("cell"))?;
+    let [grid_left, grid_top] = top_left.unwrap_or([field.left(), field.top()]);
+
+    let mut bricks = Vec::new();
+    for (row, raw_line) in lines.iter().enumerate().skip(grid_start) {
+        let grid_row = row - grid_start;
+        for (column, symbol) in raw_line.trim_end().chars().enumerate() {
+            if symbol == '.' || symbol == ' ' {
+                continue;
+            }
+            let template = templates.get(&symbol).ok_or_else(|| {
+                LevelParseError::new(row + 1, column + 1, format!("brick '{}' is not defined", symbol))
+            })?;
+            let left = grid_left + column as f32 * cell_width;
+            let top = grid_top - grid_row as f32 * cell_height;
+            let brick = Brick::new(left, top - cell_height, left + cell_width, top, template.variety.clone())
+                .with_hit_points(template.hit_points);
+            if !brick.position.grow(-0.5).within(&field) {
+                return Err(LevelParseError::new(row + 1, column + 1, format!("brick '{}' is outside the field", symbol)));
+            }
+            bricks.push(brick);
+        }
+    }
+
+    Ok(Level::new(&name.unwrap_or_default(), bricks)
+        .with_field(field)
+        .with_speed_rules(speed_rules))
+}
+
+pub fn load_level(path: &Path) -> Result<Level, LevelLoadError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| LevelLoadError::Io { path: path.to_path_buf(), error })?;
+    parse_level(&text)
+        .map_err(|error| LevelLoadError::Parse { path: path.to_path_buf(), error })
+}
+
+// Loads every *.level file in the directory, in file name order
+pub fn load_levels_from_dir(dir: &Path) -> Result<Vec<Level>, LevelLoadError> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|error| LevelLoadError::Io { path: dir.to_path_buf(), error })?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|error| LevelLoadError::Io { path: dir.to_path_buf(), error })?
+            .path();
+        if path.extension().is_some_and(|extension| extension == "level") {
+            paths.push(path);
+        }
+    }
+    if paths.is_empty() {
+        return Err(LevelLoadError::NoLevels { path: dir.to_path_buf() });
+    }
+    paths.sort();
+    paths.iter().map(|path| load_level(path)).collect()
+}
+
+pub fn builtin_levels() -> Vec<Level> {
+    [
+        include_str!("../data/levels/01-warm-up.level"),
+        include_str!("../data/levels/02-wall.level"),
+    ].iter()
+        .map(|text| parse_level(text).unwrap())
+        .collect()
+}
@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use crate::game_state::*;
+use crate::level::*;
+
+const LEVEL: &str = "name Two rows # comment
+field 0 0 200 100
+cell 40 20
+brick R standard 1 2
+brick S steel
+grid
+R.R
+SS
+";
+
+#[test]
+fn parse_level_places_bricks_on_grid() {
+    let level = parse_level(LEVEL).unwrap();
+
+    assert_eq!(level.name, "Two rows");
+    assert_eq!(level.field.right(), 200.0);
+    assert_eq!(level.bricks.len(), 4);
+    assert_eq!(level.bricks[1].position.left(), 80.0);
+    assert_eq!(level.bricks[1].position.top(), 100.0);
+    assert_eq!(level.bricks[1].max_hit_points, 2);
+    assert_eq!(level.bricks[3].position.bottom(), 60.0);
+    assert!(!level.bricks[3].is_breakable());
+}
+
+#[test]
+fn parse_level_reports_line_and_column() {
+    let tests = [
+        ("Unknown brick", LEVEL.replace("SS", "SX"), 8, 2),
+        ("Bad number", LEVEL.replace("cell 40 20", "cell 40 2o"), 3, 9),
+        ("Bad variety", LEVEL.replace("brick S steel", "brick S glass"), 5, 9),
+        ("Unknown key", LEVEL.replace("cell", "size"), 3, 1),
+        ("Outside field", LEVEL.replace("R.R", "R.R.RR"), 7, 6),
+        ("Zero cell", LEVEL.replace("cell 40 20", "cell 0 20"), 3, 1),
+        ("Negative cell", LEVEL.replace("cell 40 20", "cell 40 -20"), 3, 1),
+        ("Inverted field", LEVEL.replace("field 0 0 200 100", "field 200 0 0 100"), 2, 1),
+        ("Huge field", LEVEL.replace("field 0 0 200 100", "field 0 0 200 1e9"), 2, 1),
+        ("Top wall value", LEVEL.replace("grid", "speed_up_top_wall yes\ngrid"), 6, 19),
+    ];
+
+    for (test_name, text, expected_line, expected_column) in tests.iter() {
+        let result = parse_level(text);
+
+        assert!(matches!(&result, Err(LevelParseError { line, column, .. }) if line == expected_line && column == expected_column),
+                "Test failed {:?}. Actual: {:?}", test_name, result);
+    }
+}
+
+#[test]
+fn parse_level_requires_grid_and_field() {
+    assert!(parse_level("cell 40 20\ngrid\n").is_err());
+    assert!(parse_level("field 0 0 200 100\ncell 40 20\n").is_err());
+}
+
+#[test]
+fn shipped_levels_match_builtin_levels() {
+    let loaded = load_levels_from_dir(Path::new("data/levels")).unwrap();
+    let builtin = builtin_levels();
+
+    assert_eq!(loaded.len(), builtin.len());
+    for (loaded_level, builtin_level) in loaded.iter().zip(builtin.iter()) {
+        assert_eq!(loaded_level.name, builtin_level.name);
+        assert_eq!(loaded_level.bricks.len(), builtin_level.bricks.len());
+    }
+    assert!(GameState::with_levels(loaded).unwrap().bricks().iter().any(|brick| !brick.is_breakable()));
+}
+
+#[test]
+fn parse_level_reads_speed_rules() {
+    let level = parse_level("speed 150 100 250\nspeed_up 8 3\nspeed_up_top_wall\nfield 0 0 200 100\ncell 40 20\ngrid\n").unwrap();
+
+    assert_eq!(level.speed_rules.serve_speed, 150.0);
+    assert_eq!(level.speed_rules.max_speed, 250.0);
+    assert_eq!(level.speed_rules.speed_up_percent, 8.0);
+    assert_eq!(level.speed_rules.paddle_hits_per_speed_up, 3);
+    assert!(level.speed_rules.speed_up_on_top_wall);
+
+    let error = parse_level("speed 50 100 250\n").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 1));
+}
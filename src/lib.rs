@@ -0,0 +1,27 @@
+pub mod geometry;
+#[cfg(test)]
+mod geometry_test;
+pub mod game_state;
+#[cfg(test)]
+mod game_state_test;
+pub mod headless;
+#[cfg(test)]
+mod headless_test;
+pub mod input;
+#[cfg(test)]
+mod input_test;
+pub mod level;
+#[cfg(test)]
+mod level_test;
+pub mod paddle_bounce;
+#[cfg(test)]
+mod paddle_bounce_test;
+pub mod power_up;
+pub mod random;
+pub mod replay;
+#[cfg(test)]
+mod replay_test;
+#[cfg(feature = "serde")]
+pub mod snapshot;
+#[cfg(all(test, feature = "serde"))]
+mod snapshot_test;
@@ -5,16 +5,62 @@ extern crate allegro_ttf;
 use allegro::*;
 use std::collections::HashSet;
 
-mod geometry;
-#[cfg(test)]
-mod geometry_test;
-mod game_state;
 mod ui;
 
+use rustanoid::game_state::*;
+use rustanoid::input::*;
+use rustanoid::replay::*;
+use rustanoid::snapshot::*;
 use ui::*;
-use game_state::*;
+
+const QUICK_SAVE_PATH: &str = "quicksave.json";
+const KEY_BINDINGS_PATH: &str = "keys.cfg";
+const JOYSTICK_DEADZONE: f32 = 0.2;
+
+// Matches the names in rustanoid::input::KEY_NAMES
+fn key_name(keycode: KeyCode) -> String {
+    format!("{:?}", keycode).trim_start_matches('_').to_string()
+}
+
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
 
 allegro_main! {
+    let args: Vec<String> = std::env::args().collect();
+    let record_path = arg_value(&args, "--record");
+    // A replay plays back from the title screen, a recording that starts
+    // from a snapshot could never verify
+    if record_path.is_some() && arg_value(&args, "--snapshot").is_some() {
+        eprintln!("--record can't be combined with --snapshot");
+        std::process::exit(2);
+    }
+    let playback = arg_value(&args, "--replay").map(|path| {
+        let text = std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("Can't read replay {}: {}", path, err));
+        Replay::parse(&text)
+            .unwrap_or_else(|err| panic!("Can't parse replay {}: {}", path, err))
+    });
+    let mut playback_frames = playback.iter().flat_map(|replay| replay.frames.iter());
+    let mut recording = Replay::new();
+    let key_bindings_path = arg_value(&args, "--keys")
+        .or_else(|| std::path::Path::new(KEY_BINDINGS_PATH).exists().then(|| KEY_BINDINGS_PATH.to_string()));
+    let key_bindings = match key_bindings_path {
+        Some(path) => load_key_bindings(std::path::Path::new(&path))
+            .unwrap_or_else(|err| panic!("Can't load key bindings: {}", err)),
+        None => KeyBindings::default(),
+    };
+    for conflict in key_bindings.conflicts() {
+        eprintln!("Conflicting key binding: {}", conflict);
+    }
+    let use_joystick = args.iter().any(|arg| arg == "--joystick");
+    let joystick_deadzone = arg_value(&args, "--deadzone")
+        .map(|value| value.parse::<f32>().unwrap_or_else(|err| panic!("Can't parse deadzone {}: {}", value, err)))
+        .unwrap_or(JOYSTICK_DEADZONE);
+
     let ui_config = UIConfig::default();
     let core = Core::init().unwrap();
 
@@ -22,16 +68,34 @@ allegro_main! {
     let timer = Timer::new(&core, 1.0 / 100.0).unwrap();
     let queue = EventQueue::new(&core).unwrap();
     core.install_keyboard().unwrap();
+    core.install_mouse().unwrap();
 
     queue.register_event_source(ui.display.get_event_source());
     queue.register_event_source(timer.get_event_source());
     queue.register_event_source(core.get_keyboard_event_source().unwrap());
+    queue.register_event_source(core.get_mouse_event_source().unwrap());
+    if use_joystick {
+        core.install_joystick().unwrap();
+        queue.register_event_source(core.get_joystick_event_source().unwrap());
+    }
 
     let mut redraw = true;
     timer.start();
 
-    let mut game = GameState::make_initial();
-    let mut keys_state_set: HashSet<KeyCode> = HashSet::new();
+    let levels = rustanoid::level::load_levels_from_dir(std::path::Path::new("data/levels"))
+        .unwrap_or_else(|err| panic!("Can't load levels: {}", err));
+    let mut game = match arg_value(&args, "--snapshot") {
+        Some(path) => load_snapshot(std::path::Path::new(&path))
+            .unwrap_or_else(|err| panic!("Can't load snapshot: {}", err)),
+        None => GameState::with_levels(levels)
+            .unwrap_or_else(|| panic!("No levels to play"))
+            .with_title_screen(),
+    };
+    let mut pressed_keys: HashSet<String> = HashSet::new();
+    let mut pointer_x: Option<f32> = None;
+    let mut analog_x: f32 = 0.0;
+    let mut mouse_fire = false;
+    let mut joystick_fire = false;
 
     'exit: loop {
         if redraw && queue.is_empty() {
@@ -42,27 +106,87 @@ allegro_main! {
         let event = queue.wait_for_event();
         match event {
             KeyDown { keycode, .. } => {
-                keys_state_set.insert(keycode);
-                ();
+                let actions = key_bindings.actions_for(&key_name(keycode));
+                if actions.contains(&Action::MoveLeft) || actions.contains(&Action::MoveRight) {
+                    pointer_x = None;
+                }
+                pressed_keys.insert(key_name(keycode));
             },
             KeyUp { keycode, .. } => {
-                keys_state_set.remove(&keycode);
-                ()
+                pressed_keys.remove(&key_name(keycode));
+            },
+            MouseAxes { x, .. } => pointer_x = Some(x as f32 / ui_config.world_to_gfx_scale_factor),
+            MouseButtonDown { button: 1, .. } => mouse_fire = true,
+            MouseButtonUp { button: 1, .. } => mouse_fire = false,
+            JoystickAxes { stick: JoystickStick::Generic(0), axis: 0, pos, .. } => {
+                analog_x = analog_axis_with_deadzone(pos, joystick_deadzone);
+                if analog_x != 0.0 {
+                    pointer_x = None;
+                }
             },
+            JoystickButtonDown { .. } => joystick_fire = true,
+            JoystickButtonUp { .. } => joystick_fire = false,
             _ => (),
         };
 
         match event {
             DisplayClose{..} => break 'exit,
             TimerTick{ timestamp, .. } => {
-                game.tick(timestamp, KeyboardState::from_key_set(&keys_state_set));
-                redraw = true;
+                if playback.is_some() {
+                    if let Some(frame) = playback_frames.next() {
+                        game.tick(frame.timestamp_sec, frame.keyboard_state.clone());
+                        redraw = true;
+                    }
+                } else {
+                    let keyboard_state = KeyboardState {
+                        fire: key_bindings.is_active(Action::Fire, &pressed_keys) || mouse_fire || joystick_fire,
+                        pointer_x,
+                        analog_x,
+                        ..key_bindings.keyboard_state(&pressed_keys)
+                    };
+                    if record_path.is_some() {
+                        recording.record(timestamp, &keyboard_state);
+                    }
+                    game.tick(timestamp, keyboard_state);
+                    redraw = true;
+                }
+            },
+            KeyDown { keycode, .. } => {
+                let actions = key_bindings.actions_for(&key_name(keycode));
+                if actions.contains(&Action::Quit) {
+                    break 'exit;
+                }
+                if actions.contains(&Action::Debug) {
+                    println!("{:#?}", game);
+                }
+                if actions.contains(&Action::QuickSave) && playback.is_none() {
+                    match save_snapshot(&game, std::path::Path::new(QUICK_SAVE_PATH)) {
+                        Ok(()) => println!("Saved {}", QUICK_SAVE_PATH),
+                        Err(err) => eprintln!("Can't save snapshot: {}", err),
+                    }
+                }
+                if actions.contains(&Action::QuickLoad) && record_path.is_some() {
+                    eprintln!("Quick-load is disabled while recording a replay");
+                } else if actions.contains(&Action::QuickLoad) && playback.is_none() {
+                    match load_snapshot(std::path::Path::new(QUICK_SAVE_PATH)) {
+                        Ok(loaded) => {
+                            game = loaded;
+                            redraw = true;
+                        },
+                        Err(err) => eprintln!("Can't load snapshot: {}", err),
+                    }
+                }
             },
-            KeyDown { keycode: KeyCode::Escape, .. } =>
-                break 'exit,
-            KeyDown { keycode: KeyCode::P, .. } =>
-                println!("{:#?}", game),
             _ => (),
         }
     }
+
+    if let Some(path) = record_path {
+        recording.finish(&game);
+        std::fs::write(&path, recording.to_text())
+            .unwrap_or_else(|err| panic!("Can't write replay {}: {}", path, err));
+    }
+    if let Some(replay) = &playback {
+        println!("Replay final state hash: {:016x}, recorded: {:?}", state_hash(&game), replay.final_state_hash.map(|hash| format!("{:016x}", hash)));
+    }
 }
@@ -0,0 +1,49 @@
+use crate::geometry::*;
+
+// How the ball leaves the top face of the paddle. Side and corner hits
+// always reflect off the collision normal.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PaddleBounce {
+    // Reflects off a normal tilted towards the side that was hit
+    NormalRotation { max_rotation: f32 },
+    // Classic: the outgoing angle only depends on the hit position, straight
+    // up in the middle and max_angle from vertical at the edges
+    HitPosition { max_angle: f32 },
+    // Reflects and adds a share of the paddle velocity, so a moving paddle
+    // drags the ball along
+    VelocityTransfer { transfer: f32 },
+}
+
+impl Default for PaddleBounce {
+    fn default() -> PaddleBounce {
+        PaddleBounce::NormalRotation { max_rotation: std::f32::consts::FRAC_PI_8 / 2.0 }
+    }
+}
+
+impl PaddleBounce {
+    // hit_offset is -1 at the left edge of the paddle, 0 in the middle and 1 at the right edge
+    pub fn outgoing_vector(&self, incoming: &FVector2d, hit_offset: f32, paddle_vector: &FVector2d) -> FVector2d {
+        let hit_offset = hit_offset.clamp(-1.0, 1.0);
+        let up = FVector2d::new(0.0, 1.0);
+        match self {
+            PaddleBounce::NormalRotation { max_rotation } => {
+                let collision = Collision {
+                    point: FPoint::new(0.0, 0.0),
+                    normal: up.rotate_clockwise(max_rotation * hit_offset),
+                    kind: CollisionKind::Flat,
+                    depth: 0.0,
+                };
+                new_vector_after_circle_collision(incoming, &collision)
+            },
+            PaddleBounce::HitPosition { max_angle } => {
+                up.mul_scalar(incoming.length()).rotate_clockwise(max_angle * hit_offset)
+            },
+            PaddleBounce::VelocityTransfer { transfer } => {
+                let collision = Collision { point: FPoint::new(0.0, 0.0), normal: up, kind: CollisionKind::Flat, depth: 0.0 };
+                new_vector_after_circle_collision(incoming, &collision)
+                    .plus(&paddle_vector.clone().mul_scalar(*transfer))
+            },
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::f32::consts::{FRAC_PI_3, FRAC_PI_4, FRAC_PI_8};
+
+use crate::geometry::*;
+use crate::paddle_bounce::*;
+
+// Clockwise angle from straight up, positive to the right
+fn angle_from_vertical(vector: &FVector2d) -> f32 {
+    vector.x.atan2(vector.y)
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 10e-5, "Actual: {}, expected: {}", actual, expected);
+}
+
+#[test]
+fn hit_position_maps_offset_to_outgoing_angle() {
+    let bounce = PaddleBounce::HitPosition { max_angle: FRAC_PI_3 };
+    let incoming = FVector2d::new(30.0, -100.0);
+
+    for (hit_offset, expected_angle) in [(-1.0, -FRAC_PI_3), (-0.5, -FRAC_PI_3 / 2.0), (0.0, 0.0), (0.5, FRAC_PI_3 / 2.0), (1.0, FRAC_PI_3)] {
+        let outgoing = bounce.outgoing_vector(&incoming, hit_offset, &FVector2d::zero());
+
+        assert_close(angle_from_vertical(&outgoing), expected_angle);
+        assert_close(outgoing.length(), incoming.length());
+    }
+}
+
+#[test]
+fn hit_position_ignores_incoming_direction_and_paddle_movement() {
+    let bounce = PaddleBounce::HitPosition { max_angle: FRAC_PI_3 };
+    let paddle_vector = FVector2d::new(200.0, 0.0);
+
+    let from_left = bounce.outgoing_vector(&FVector2d::new(80.0, -60.0), 0.25, &paddle_vector);
+    let from_right = bounce.outgoing_vector(&FVector2d::new(-80.0, -60.0), 0.25, &paddle_vector);
+
+    assert_close(angle_from_vertical(&from_left), FRAC_PI_3 / 4.0);
+    assert_close(angle_from_vertical(&from_right), FRAC_PI_3 / 4.0);
+}
+
+#[test]
+fn hit_offset_beyond_the_edges_is_clamped() {
+    let bounce = PaddleBounce::HitPosition { max_angle: FRAC_PI_3 };
+
+    let outgoing = bounce.outgoing_vector(&FVector2d::new(0.0, -100.0), -1.4, &FVector2d::zero());
+
+    assert_close(angle_from_vertical(&outgoing), -FRAC_PI_3);
+}
+
+#[test]
+fn normal_rotation_reflects_center_hits_like_a_wall() {
+    let bounce = PaddleBounce::NormalRotation { max_rotation: FRAC_PI_8 };
+
+    let outgoing = bounce.outgoing_vector(&FVector2d::new(50.0, -100.0), 0.0, &FVector2d::zero());
+
+    assert_close(outgoing.x, 50.0);
+    assert_close(outgoing.y, 100.0);
+}
+
+#[test]
+fn normal_rotation_turns_a_vertical_ball_by_twice_the_tilt() {
+    let bounce = PaddleBounce::NormalRotation { max_rotation: FRAC_PI_8 };
+
+    let right_edge = bounce.outgoing_vector(&FVector2d::new(0.0, -100.0), 1.0, &FVector2d::zero());
+    let left_half = bounce.outgoing_vector(&FVector2d::new(0.0, -100.0), -0.5, &FVector2d::zero());
+
+    assert_close(angle_from_vertical(&right_edge), FRAC_PI_4);
+    assert_close(angle_from_vertical(&left_half), -FRAC_PI_8);
+}
+
+#[test]
+fn velocity_transfer_adds_a_share_of_the_paddle_vector() {
+    let bounce = PaddleBounce::VelocityTransfer { transfer: 0.5 };
+
+    let moving = bounce.outgoing_vector(&FVector2d::new(0.0, -100.0), 0.8, &FVector2d::new(200.0, 0.0));
+    let still = bounce.outgoing_vector(&FVector2d::new(-40.0, -100.0), 0.8, &FVector2d::zero());
+
+    assert_close(angle_from_vertical(&moving), FRAC_PI_4);
+    assert_close(still.x, -40.0);
+    assert_close(still.y, 100.0);
+}
@@ -0,0 +1,110 @@
+use crate::geometry::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PowerUpKind {
+    Expand,
+    Shrink,
+    Slow,
+    Catch,
+    Laser,
+    Multiball,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 6] = [
+        PowerUpKind::Expand,
+        PowerUpKind::Shrink,
+        PowerUpKind::Slow,
+        PowerUpKind::Catch,
+        PowerUpKind::Laser,
+        PowerUpKind::Multiball,
+    ];
+
+    pub fn is_timed(&self) -> bool {
+        !matches!(self, PowerUpKind::Multiball)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capsule {
+    pub position: Rectangle,
+    pub kind: PowerUpKind,
+}
+
+impl Capsule {
+    pub const WIDTH: f32 = 16.0;
+    pub const HEIGHT: f32 = 6.0;
+
+    pub fn new(center: &FPoint, kind: PowerUpKind) -> Capsule {
+        Capsule {
+            position: Rectangle::make_by_size(
+                center.x - Capsule::WIDTH / 2.0,
+                center.y - Capsule::HEIGHT / 2.0,
+                Capsule::WIDTH,
+                Capsule::HEIGHT
+            ),
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Projectile {
+    pub position: Rectangle,
+}
+
+impl Projectile {
+    pub const WIDTH: f32 = 2.0;
+    pub const HEIGHT: f32 = 6.0;
+
+    pub fn new(bottom_center: &FPoint) -> Projectile {
+        Projectile {
+            position: Rectangle::make_by_size(
+                bottom_center.x - Projectile::WIDTH / 2.0,
+                bottom_center.y,
+                Projectile::WIDTH,
+                Projectile::HEIGHT
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActiveEffect {
+    pub kind: PowerUpKind,
+    pub time_left_sec: f32,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerUpRules {
+    pub drop_chance: f32,
+    pub fall_speed: f32,
+    pub effect_duration_sec: f32,
+    pub expand_factor: f32,
+    pub shrink_factor: f32,
+    pub slow_factor: f32,
+    pub multiball_spread_angle: f32,
+    pub laser_cooldown_sec: f32,
+    pub laser_speed: f32,
+}
+
+impl Default for PowerUpRules {
+    fn default() -> PowerUpRules {
+        PowerUpRules {
+            drop_chance: 0.2,
+            fall_speed: 60.0,
+            effect_duration_sec: 15.0,
+            expand_factor: 1.5,
+            shrink_factor: 0.6,
+            slow_factor: 0.6,
+            multiball_spread_angle: std::f32::consts::FRAC_PI_6,
+            laser_cooldown_sec: 0.3,
+            laser_speed: 300.0,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use crate::game_state::*;
+
+const HEADER: &str = "rustanoid-replay 1";
+
+#[derive(Debug, Clone)]
+pub struct ReplayFrame {
+    pub timestamp_sec: f64,
+    pub keyboard_state: KeyboardState,
+}
+
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub frames: Vec<ReplayFrame>,
+    pub final_state_hash: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct ReplayParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ReplayParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReplayVerification {
+    Match { hash: u64 },
+    Mismatch { expected: u64, actual: u64 },
+    NoHashRecorded { actual: u64 },
+}
+
+impl Replay {
+    pub fn new() -> Replay {
+        Replay {
+            frames: Vec::new(),
+            final_state_hash: None,
+        }
+    }
+
+    pub fn record(&mut self, timestamp_sec: f64, keyboard_state: &KeyboardState) {
+        self.frames.push(ReplayFrame {
+            timestamp_sec,
+            keyboard_state: keyboard_state.clone(),
+        });
+    }
+
+    pub fn finish(&mut self, game: &GameState) {
+        self.final_state_hash = Some(state_hash(game));
+    }
+
+    // One frame per line: "<timestamp> <keys> [x=<pointer x>] [axis=<analog x>]",
+    // keys are any of L, R, F, P or '-' for none.
+    // Timestamps use the shortest representation that parses back to the same f64.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from(HEADER);
+        text.push('\n');
+        for frame in self.frames.iter() {
+            let keys = &frame.keyboard_state;
+            let mut flags = String::new();
+            if keys.move_left { flags.push('L'); }
+            if keys.move_right { flags.push('R'); }
+            if keys.fire { flags.push('F'); }
+            if keys.pause { flags.push('P'); }
+            if flags.is_empty() { flags.push('-'); }
+            text.push_str(&format!("{:?} {}", frame.timestamp_sec, flags));
+            if let Some(pointer_x) = keys.pointer_x {
+                text.push_str(&format!(" x={:?}", pointer_x));
+            }
+            if keys.analog_x != 0.0 {
+                text.push_str(&format!(" axis={:?}", keys.analog_x));
+            }
+            text.push('\n');
+        }
+        if let Some(hash) = self.final_state_hash {
+            text.push_str(&format!("hash {:016x}\n", hash));
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Replay, ReplayParseError> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, HEADER)) => (),
+            _ => return Err(ReplayParseError { line: 1, message: format!("expected '{}'", HEADER) }),
+        }
+        let mut replay = Replay::new();
+        for (index, line) in lines {
+            let error = |message: String| ReplayParseError { line: index + 1, message };
+            if replay.final_state_hash.is_some() {
+                return Err(error("unexpected data after hash".to_string()));
+            }
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (None, _) => continue,
+                (Some("hash"), Some(hash)) if words.next().is_none() => {
+                    let parsed_hash = u64::from_str_radix(hash, 16)
+                        .map_err(|_| error(format!("invalid hash '{}'", hash)))?;
+                    replay.final_state_hash = Some(parsed_hash);
+                },
+                (Some(timestamp), Some(flags)) => {
+                    let timestamp_sec = timestamp.parse::<f64>()
+                        .map_err(|_| error(format!("invalid timestamp '{}'", timestamp)))?;
+                    let mut keyboard_state = KeyboardState::none();
+                    for flag in flags.chars() {
+                        match flag {
+                            'L' => keyboard_state.move_left = true,
+                            'R' => keyboard_state.move_right = true,
+                            'F' => keyboard_state.fire = true,
+                            'P' => keyboard_state.pause = true,
+                            '-' => (),
+                            _ => return Err(error(format!("unknown key flag '{}'", flag))),
+                        }
+                    }
+                    for word in words {
+                        let parse_value = |value: &str| value.parse::<f32>()
+                            .map_err(|_| error(format!("invalid value in '{}'", word)));
+                        match word.split_once('=') {
+                            Some(("x", value)) => keyboard_state.pointer_x = Some(parse_value(value)?),
+                            Some(("axis", value)) => keyboard_state.analog_x = parse_value(value)?,
+                            _ => return Err(error(format!("unknown field '{}'", word))),
+                        }
+                    }
+                    replay.frames.push(ReplayFrame { timestamp_sec, keyboard_state });
+                },
+                _ => return Err(error("expected '<timestamp> <keys>' or 'hash <hex>'".to_string())),
+            }
+        }
+        Ok(replay)
+    }
+
+    pub fn play(&self, game: &mut GameState) -> u64 {
+        for frame in self.frames.iter() {
+            game.tick(frame.timestamp_sec, frame.keyboard_state.clone());
+        }
+        state_hash(game)
+    }
+
+    pub fn verify(&self, game: &mut GameState) -> ReplayVerification {
+        let actual = self.play(game);
+        match self.final_state_hash {
+            Some(expected) if expected == actual => ReplayVerification::Match { hash: actual },
+            Some(expected) => ReplayVerification::Mismatch { expected, actual },
+            None => ReplayVerification::NoHashRecorded { actual },
+        }
+    }
+}
+
+impl Default for Replay {
+    fn default() -> Replay {
+        Replay::new()
+    }
+}
+
+// FNV-1a, stable across runs and platforms unlike the std hashers
+struct StateHasher {
+    hash: u64,
+}
+
+impl StateHasher {
+    fn new() -> StateHasher {
+        StateHasher { hash: 0xcbf2_9ce4_8422_2325 }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_f32(&mut self, value: f32) {
+        self.write(&value.to_bits().to_le_bytes());
+    }
+
+    fn write_f64(&mut self, value: f64) {
+        self.write(&value.to_bits().to_le_bytes());
+    }
+
+    fn write_i64(&mut self, value: i64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_bool(&mut self, value: bool) {
+        self.write(&[value as u8]);
+    }
+
+    fn write_option_f32(&mut self, value: Option<f32>) {
+        self.write_bool(value.is_some());
+        self.write_f32(value.unwrap_or(0.0));
+    }
+}
+
+// Covers everything that feeds the next steps, rules and levels aside
+pub fn state_hash(game: &GameState) -> u64 {
+    let mut hasher = StateHasher::new();
+    hasher.write(format!("{:?}", game.time_state).as_bytes());
+    hasher.write_i64(game.lives_left as i64);
+    hasher.write_i64(game.score as i64);
+    hasher.write_i64(game.combo_hits as i64);
+    hasher.write_i64(game.extra_lives_awarded as i64);
+    hasher.write_i64(game.paddle_hits as i64);
+    hasher.write_i64(game.current_level as i64);
+    hasher.write(&game.random.state.to_le_bytes());
+    for ball in game.balls.iter() {
+        hasher.write_f32(ball.position.center.x);
+        hasher.write_f32(ball.position.center.y);
+        hasher.write_f32(ball.position.radius);
+        hasher.write_f32(ball.movement_vector.x);
+        hasher.write_f32(ball.movement_vector.y);
+        hasher.write_option_f32(ball.stuck_offset);
+    }
+    for brick in game.bricks().iter() {
+        hasher.write_f32(brick.position.left());
+        hasher.write_f32(brick.position.bottom());
+        hasher.write_i64(brick.hit_points as i64);
+    }
+    hasher.write_f32(game.paddle.position.left());
+    hasher.write_f32(game.paddle.position.right());
+    hasher.write_f32(game.paddle.vector.x);
+    hasher.write_f32(game.paddle_input_x);
+    hasher.write_option_f32(game.paddle_target_x);
+    for capsule in game.capsules.iter() {
+        hasher.write(format!("{:?}", capsule.kind).as_bytes());
+        hasher.write_f32(capsule.position.left());
+        hasher.write_f32(capsule.position.bottom());
+    }
+    for effect in game.active_effects.iter() {
+        hasher.write(format!("{:?}", effect.kind).as_bytes());
+        hasher.write_f32(effect.time_left_sec);
+    }
+    for projectile in game.projectiles.iter() {
+        hasher.write_f32(projectile.position.left());
+        hasher.write_f32(projectile.position.bottom());
+    }
+    hasher.write_f32(game.laser_cooldown_left_sec);
+    hasher.write_f32(game.serve_aim_angle);
+    hasher.write_bool(game.fire_was_down);
+    hasher.write_bool(game.pause_was_down);
+    hasher.write_f64(game.accumulator_sec);
+    hasher.hash
+}
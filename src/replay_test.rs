@@ -0,0 +1,87 @@
+use crate::game_state::*;
+use crate::geometry::*;
+use crate::headless::*;
+use crate::power_up::*;
+use crate::replay::*;
+
+fn recorded_session() -> (Replay, GameState) {
+    let script = InputScript::parse("1 fire\n120 right\n80 left fire\n10 pause\n30\n5 pause\n60 x=180.25\n40 axis=-0.7\n200\n").unwrap();
+    let mut clock = FixedStepClock::new(0.0137);
+    let mut game = GameState::make_initial();
+    let mut replay = Replay::new();
+    for step in script.steps.iter() {
+        for _ in 0..step.ticks {
+            let timestamp_sec = clock.advance();
+            replay.record(timestamp_sec, &step.keyboard_state);
+            game.tick(timestamp_sec, step.keyboard_state.clone());
+        }
+    }
+    replay.finish(&game);
+    (replay, game)
+}
+
+#[test]
+fn replay_text_round_trips_exactly() {
+    let (replay, _) = recorded_session();
+
+    let parsed = Replay::parse(&replay.to_text()).unwrap();
+
+    assert_eq!(parsed.frames.len(), replay.frames.len());
+    assert!(parsed.frames.iter().zip(replay.frames.iter()).all(|(parsed_frame, frame)| {
+        parsed_frame.timestamp_sec.to_bits() == frame.timestamp_sec.to_bits()
+            && parsed_frame.keyboard_state.fire == frame.keyboard_state.fire
+            && parsed_frame.keyboard_state.move_left == frame.keyboard_state.move_left
+            && parsed_frame.keyboard_state.move_right == frame.keyboard_state.move_right
+            && parsed_frame.keyboard_state.pause == frame.keyboard_state.pause
+            && parsed_frame.keyboard_state.pointer_x == frame.keyboard_state.pointer_x
+            && parsed_frame.keyboard_state.analog_x.to_bits() == frame.keyboard_state.analog_x.to_bits()
+    }));
+    assert_eq!(parsed.final_state_hash, replay.final_state_hash);
+}
+
+#[test]
+fn playback_reproduces_recorded_session() {
+    let (replay, recorded_game) = recorded_session();
+    let parsed = Replay::parse(&replay.to_text()).unwrap();
+
+    let verification = parsed.verify(&mut GameState::make_initial());
+
+    assert_eq!(verification, ReplayVerification::Match { hash: state_hash(&recorded_game) });
+}
+
+#[test]
+fn tampered_replay_fails_verification() {
+    let (mut replay, _) = recorded_session();
+    replay.frames[0].keyboard_state.fire = false;
+
+    let verification = replay.verify(&mut GameState::make_initial());
+
+    assert!(matches!(verification, ReplayVerification::Mismatch { .. }), "Actual: {:?}", verification);
+}
+
+#[test]
+fn state_hash_covers_random_and_power_up_state() {
+    let (_, game) = recorded_session();
+    let hash = state_hash(&game);
+    let changes: [fn(&mut GameState); 5] = [
+        |game| game.random.state ^= 1,
+        |game| game.projectiles.push(Projectile::new(&FPoint::new(100.0, 100.0))),
+        |game| game.active_effects.push(ActiveEffect { kind: PowerUpKind::Laser, time_left_sec: 1.0 }),
+        |game| game.combo_hits += 1,
+        |game| game.serve_aim_angle += 0.1,
+    ];
+
+    for change in changes {
+        let (_, mut changed) = recorded_session();
+        change(&mut changed);
+        assert_ne!(state_hash(&changed), hash);
+    }
+}
+
+#[test]
+fn replay_parse_reports_line() {
+    let result = Replay::parse("rustanoid-replay 1\n0.01 F\n0.02 X\n");
+
+    assert!(matches!(result, Err(ReplayParseError { line: 3, .. })), "Actual: {:?}", result);
+    assert!(Replay::parse("0.01 F\n").is_err());
+}
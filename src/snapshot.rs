@@ -0,0 +1,47 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::game_state::*;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io { path: PathBuf, error: std::io::Error },
+    Format { path: PathBuf, error: serde_json::Error },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            SnapshotError::Format { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+pub fn to_json(game: &GameState) -> Result<String, serde_json::Error> {
+    serde_json::to_string(game)
+}
+
+// The saved clock belongs to whatever timer was running when the snapshot was
+// taken, so a running game comes back stopped and restarts on the next tick.
+pub fn from_json(text: &str) -> Result<GameState, serde_json::Error> {
+    let mut game: GameState = serde_json::from_str(text)?;
+    if let TimeState::Running { .. } = game.time_state {
+        game.time_state = TimeState::Stopped;
+    }
+    Ok(game)
+}
+
+pub fn save_snapshot(game: &GameState, path: &Path) -> Result<(), SnapshotError> {
+    let text = to_json(game)
+        .map_err(|error| SnapshotError::Format { path: path.to_path_buf(), error })?;
+    std::fs::write(path, text)
+        .map_err(|error| SnapshotError::Io { path: path.to_path_buf(), error })
+}
+
+pub fn load_snapshot(path: &Path) -> Result<GameState, SnapshotError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| SnapshotError::Io { path: path.to_path_buf(), error })?;
+    from_json(&text)
+        .map_err(|error| SnapshotError::Format { path: path.to_path_buf(), error })
+}
@@ -0,0 +1,82 @@
+use crate::game_state::*;
+use crate::headless::*;
+use crate::replay::*;
+use crate::snapshot::*;
+
+fn game_after_script(script_text: &str, clock: &mut FixedStepClock) -> GameState {
+    let mut game = GameState::make_initial();
+    run(&mut game, clock, &InputScript::parse(script_text).unwrap());
+    game
+}
+
+#[test]
+fn loaded_snapshot_comes_back_stopped() {
+    let mut clock = FixedStepClock::new(0.01);
+    let game = game_after_script("1 fire\n50 right\n", &mut clock);
+    assert!(matches!(game.time_state, TimeState::Running { .. }));
+
+    let loaded = from_json(&to_json(&game).unwrap()).unwrap();
+
+    assert_eq!(loaded.time_state, TimeState::Stopped);
+    assert_eq!(loaded.score, game.score);
+    assert_eq!(loaded.balls.len(), game.balls.len());
+}
+
+#[test]
+fn loaded_snapshot_plays_on_like_the_original() {
+    let mut clock = FixedStepClock::new(0.0137);
+    let mut game = game_after_script("1 fire\n120 right\n80 left\n", &mut clock);
+    let mut loaded = from_json(&to_json(&game).unwrap()).unwrap();
+    game.time_state = TimeState::Stopped;
+    assert_eq!(state_hash(&loaded), state_hash(&game));
+
+    let script = InputScript::parse("200 right\n100 left fire\n").unwrap();
+    let mut loaded_clock = FixedStepClock::new(clock.step_sec);
+    loaded_clock.now_sec = clock.now_sec;
+    run(&mut game, &mut clock, &script);
+    run(&mut loaded, &mut loaded_clock, &script);
+
+    assert_eq!(state_hash(&loaded), state_hash(&game));
+    assert_eq!(to_json(&loaded).unwrap(), to_json(&game).unwrap());
+}
+
+#[test]
+fn deserialized_game_rebuilds_the_brick_grid() {
+    let mut clock = FixedStepClock::new(0.01);
+    let mut game = game_after_script("1 fire\n", &mut clock);
+    let json = to_json(&game).unwrap();
+    assert!(!json.contains("brick_grid"));
+    let mut loaded: GameState = serde_json::from_str(&json).unwrap();
+
+    let script = InputScript::parse("300 right\n1 fire\n300 left\n1 fire\n300 right\n").unwrap();
+    let mut loaded_clock = FixedStepClock::new(clock.step_sec);
+    loaded_clock.now_sec = clock.now_sec;
+    run(&mut game, &mut clock, &script);
+    run(&mut loaded, &mut loaded_clock, &script);
+
+    assert!(game.bricks().len() < GameState::make_initial().bricks().len());
+    assert_eq!(loaded.bricks().len(), game.bricks().len());
+}
+
+#[test]
+fn malformed_snapshot_is_rejected() {
+    assert!(from_json("{\"balls\": 3}").is_err());
+}
+
+#[test]
+fn snapshot_with_a_bad_level_index_is_rejected() {
+    let last_level = GameState::make_initial().levels.len() - 1;
+    let snapshot_with = |changes: serde_json::Value| {
+        let mut snapshot: serde_json::Value = serde_json::from_str(&to_json(&GameState::make_initial()).unwrap()).unwrap();
+        for (key, value) in changes.as_object().unwrap() {
+            snapshot[key] = value.clone();
+        }
+        from_json(&snapshot.to_string())
+    };
+
+    assert!(snapshot_with(serde_json::json!({ "levels": [] })).is_err());
+    assert!(snapshot_with(serde_json::json!({ "current_level": last_level + 1 })).is_err());
+    assert!(snapshot_with(serde_json::json!({ "current_level": last_level, "time_state": "LevelComplete" })).is_err());
+    assert!(snapshot_with(serde_json::json!({ "current_level": last_level })).is_ok());
+    assert!(snapshot_with(serde_json::json!({ "time_state": "LevelComplete" })).is_ok());
+}
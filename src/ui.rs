@@ -8,8 +8,9 @@ use allegro_font::*;
 use allegro_ttf::*;
 use allegro_primitives::*;
 
-use crate::geometry::*;
-use crate::game_state::*;
+use rustanoid::geometry::*;
+use rustanoid::game_state::*;
+use rustanoid::power_up::*;
 
 pub const WORLD_SCREEN_SIZE: ISize = ISize {
     w: 640,
@@ -84,11 +85,27 @@ impl<'a> UI<'a> {
 
     pub fn render(&self, game_state: &GameState) -> () {
         self.core.clear_to_color(Color::from_rgb_f(0.0, 0.0, 0.0));
+        if game_state.time_state == TimeState::Title {
+            self.render_title(&game_state);
+            self.core.flip_display();
+            return;
+        }
+        let frame = game_state.interpolated_frame();
         self.render_walls(&game_state);
-        self.render_paddle(&game_state.paddle);
-        self.render_bricks(&game_state.bricks);
-        self.render_balls(&game_state.balls);
+        self.render_paddle(&frame.paddle_position);
+        self.render_bricks(game_state.bricks());
+        self.render_capsules(&game_state.capsules);
+        self.render_projectiles(&game_state.projectiles);
+        self.render_balls(&frame.balls);
+        self.render_serve_aim(&game_state, &frame.balls);
+        self.render_hud(&game_state);
+        self.render_lives(&game_state);
+        self.render_active_effects(&game_state.active_effects);
+        self.render_paused(&game_state);
+        self.render_life_lost(&game_state);
         self.render_game_over(&game_state);
+        self.render_level_complete(&game_state);
+        self.render_victory(&game_state);
         self.render_debug(&game_state);
         self.core.flip_display();
     }
@@ -106,12 +123,12 @@ impl<'a> UI<'a> {
         self.primitives_addon.draw_filled_rectangle(rect.x1, rect.y1, rect.x2, rect.y2, color);
     }
 
-    fn render_paddle(&self, paddle: &Paddle) {
-        let gfx_rect = paddle.position.world_to_gfx(&self.ui_config);
+    fn render_paddle(&self, paddle_position: &Rectangle) {
+        let gfx_rect = paddle_position.world_to_gfx(&self.ui_config);
         self.render_filled_rect(&gfx_rect, Color::from_rgb(200, 200, 0));
     }
 
-    fn render_bricks(&self, bricks: &Vec<Brick>) {
+    fn render_bricks(&self, bricks: &[Brick]) {
         for brick in bricks {
             let color = match brick {
                 Brick { variety: BrickVariety::Standard { color }, .. } =>
@@ -123,10 +140,53 @@ impl<'a> UI<'a> {
                     },
                 Brick { variety: BrickVariety::Steel, .. } => Color::from_rgb(150, 150, 150)
             };
+            let damage_taken = brick.damage_taken();
+            let faded_color = self.darken(color, (100 - 50 * damage_taken / brick.max_hit_points) as u32);
             let gfx_outer_rect = brick.position.world_to_gfx(&self.ui_config);
             self.render_filled_rect(&gfx_outer_rect, self.darken(color, 50));
             let gfx_inner_rect = brick.position.grow(-1.0).world_to_gfx(&self.ui_config);
-            self.render_filled_rect(&gfx_inner_rect, color);
+            self.render_filled_rect(&gfx_inner_rect, faded_color);
+            self.render_cracks(&brick.position, damage_taken, self.darken(color, 30));
+        }
+    }
+
+    fn render_cracks(&self, brick_position: &Rectangle, damage_taken: i32, color: Color) {
+        let width = brick_position.right() - brick_position.left();
+        for crack in 0..damage_taken {
+            let crack_x = brick_position.left() + width * (crack + 1) as f32 / (damage_taken + 1) as f32;
+            let start = FPoint::new(crack_x, brick_position.top()).world_to_gfx(&self.ui_config);
+            let end = FPoint::new(crack_x - width * 0.15, brick_position.bottom()).world_to_gfx(&self.ui_config);
+            self.primitives_addon.draw_line(start.x, start.y, end.x, end.y, color, self.ui_config.world_to_gfx_scale_factor);
+        }
+    }
+
+    fn power_up_look(&self, kind: PowerUpKind) -> (Color, &'static str) {
+        match kind {
+            PowerUpKind::Expand => (Color::from_rgb(50, 100, 255), "E"),
+            PowerUpKind::Shrink => (Color::from_rgb(230, 30, 30), "R"),
+            PowerUpKind::Slow => (Color::from_rgb(255, 150, 0), "S"),
+            PowerUpKind::Catch => (Color::from_rgb(0, 200, 0), "C"),
+            PowerUpKind::Laser => (Color::from_rgb(230, 0, 230), "L"),
+            PowerUpKind::Multiball => (Color::from_rgb(0, 200, 200), "M"),
+        }
+    }
+
+    fn render_capsules(&self, capsules: &Vec<Capsule>) {
+        for capsule in capsules {
+            let (color, letter) = self.power_up_look(capsule.kind);
+            let gfx_rect = capsule.position.world_to_gfx(&self.ui_config);
+            let corner_radius = Capsule::HEIGHT * self.ui_config.world_to_gfx_scale_factor / 2.0;
+            self.primitives_addon.draw_filled_rounded_rectangle(gfx_rect.x1, gfx_rect.y1, gfx_rect.x2, gfx_rect.y2, corner_radius, corner_radius, color);
+            let center = capsule.position.center().world_to_gfx(&self.ui_config);
+            let text_y = center.y - self.debug_font.get_line_height() as f32 / 2.0;
+            self.core.draw_text(&self.debug_font, Color::from_rgb(255, 255, 255), center.x, text_y, FontAlign::Centre, letter);
+        }
+    }
+
+    fn render_projectiles(&self, projectiles: &Vec<Projectile>) {
+        for projectile in projectiles {
+            let gfx_rect = projectile.position.world_to_gfx(&self.ui_config);
+            self.render_filled_rect(&gfx_rect, Color::from_rgb(255, 80, 255));
         }
     }
 
@@ -151,14 +211,93 @@ impl<'a> UI<'a> {
        }
     }
 
+    fn render_hud(&self, game_state: &GameState) {
+        let label_color = Color::from_rgb(180, 180, 180);
+        self.core.draw_text(&self.debug_font, label_color, 1060.0, 40.0, FontAlign::Left, "SCORE");
+        let score_text = format!("{}", game_state.score);
+        self.core.draw_text(&self.font, Color::from_rgb(255, 255, 255), 1060.0, 70.0, FontAlign::Left, &score_text);
+        let combo_multiplier = game_state.score_rules.combo_multiplier(game_state.combo_hits);
+        if combo_multiplier > 1 {
+            let combo_text = format!("COMBO x{}", combo_multiplier);
+            self.core.draw_text(&self.debug_font, Color::from_rgb(230, 200, 30), 1060.0, 170.0, FontAlign::Left, &combo_text);
+        }
+    }
+
+    fn render_lives(&self, game_state: &GameState) {
+        self.core.draw_text(&self.debug_font, Color::from_rgb(180, 180, 180), 1060.0, 230.0, FontAlign::Left, "LIVES");
+        let icon_width = 20.0 * self.ui_config.world_to_gfx_scale_factor;
+        let icon_height = 4.0 * self.ui_config.world_to_gfx_scale_factor;
+        for life in 0..game_state.lives_left {
+            let left = 1060.0 + life as f32 * (icon_width + 10.0);
+            let icon = Rectangle::make_by_size(left, 270.0, icon_width, icon_height);
+            self.render_filled_rect(&icon, Color::from_rgb(200, 200, 0));
+        }
+    }
+
+    fn render_active_effects(&self, active_effects: &Vec<ActiveEffect>) {
+        for (index, effect) in active_effects.iter().enumerate() {
+            let (color, letter) = self.power_up_look(effect.kind);
+            let text = format!("{} {:.0}s", letter, effect.time_left_sec.ceil());
+            self.core.draw_text(&self.debug_font, color, 1060.0, 330.0 + index as f32 * 30.0, FontAlign::Left, &text);
+        }
+    }
+
+    fn render_serve_aim(&self, game_state: &GameState, balls: &Vec<Ball>) {
+        let aim_vector = game_state.serve_vector(30.0);
+        for ball in balls.iter().filter(|ball| ball.is_stuck()) {
+            let start = ball.position.center.world_to_gfx(&self.ui_config);
+            let end = ball.position.center.add(aim_vector.clone()).world_to_gfx(&self.ui_config);
+            self.primitives_addon.draw_line(start.x, start.y, end.x, end.y, Color::from_rgb(120, 120, 120), 2.0);
+        }
+    }
+
     fn render_debug(&self, game_state: &GameState) {
         let text = format!("{:?}", game_state.balls[0].movement_vector.as_polar());
         self.core.draw_text(&self.debug_font, Color::from_rgb(255, 255, 255), 1060.0, 1000.0, FontAlign::Left, &text);
     }
 
+    fn render_title(&self, game_state: &GameState) {
+        self.core.draw_text(&self.font, Color::from_rgb(200, 200, 0), 50.0, 100.0, FontAlign::Left, "RUSTANOID");
+        let levels_text = format!("{} levels", game_state.levels.len());
+        self.core.draw_text(&self.debug_font, Color::from_rgb(180, 180, 180), 50.0, 200.0, FontAlign::Left, &levels_text);
+        self.core.draw_text(&self.debug_font, Color::from_rgb(255, 255, 255), 50.0, 250.0, FontAlign::Left, "Press fire to start");
+    }
+
+    fn render_paused(&self, game_state: &GameState) {
+        if game_state.time_state == TimeState::Paused {
+            self.core.draw_text(&self.font, Color::from_rgb(180, 180, 180), 50.0, 100.0, FontAlign::Left, "PAUSED");
+            self.core.draw_text(&self.debug_font, Color::from_rgb(255, 255, 255), 50.0, 200.0, FontAlign::Left, "Press pause to continue");
+        }
+    }
+
+    fn render_life_lost(&self, game_state: &GameState) {
+        if game_state.time_state == TimeState::LifeLost {
+            self.core.draw_text(&self.font, Color::from_rgb(230, 120, 30), 50.0, 100.0, FontAlign::Left, "BALL LOST");
+            let text = format!("{} left. Press fire to continue", game_state.lives_left);
+            self.core.draw_text(&self.debug_font, Color::from_rgb(255, 255, 255), 50.0, 200.0, FontAlign::Left, &text);
+        }
+    }
+
     fn render_game_over(&self, game_state: &GameState) {
         if game_state.time_state == TimeState::GameOver {
             self.core.draw_text(&self.font, Color::from_rgb(230, 30, 30), 50.0, 100.0, FontAlign::Left, "GAME OVER");
+            self.core.draw_text(&self.debug_font, Color::from_rgb(255, 255, 255), 50.0, 200.0, FontAlign::Left, "Press fire to play again");
+        }
+    }
+
+    fn render_level_complete(&self, game_state: &GameState) {
+        if game_state.time_state == TimeState::LevelComplete {
+            let next_level = &game_state.levels[game_state.current_level + 1];
+            self.core.draw_text(&self.font, Color::from_rgb(30, 230, 30), 50.0, 100.0, FontAlign::Left, "LEVEL COMPLETE");
+            let text = format!("Next: {}. Press fire to continue", next_level.name);
+            self.core.draw_text(&self.debug_font, Color::from_rgb(255, 255, 255), 50.0, 200.0, FontAlign::Left, &text);
+        }
+    }
+
+    fn render_victory(&self, game_state: &GameState) {
+        if game_state.time_state == TimeState::Victory {
+            self.core.draw_text(&self.font, Color::from_rgb(230, 200, 30), 50.0, 100.0, FontAlign::Left, "VICTORY");
+            self.core.draw_text(&self.debug_font, Color::from_rgb(255, 255, 255), 50.0, 200.0, FontAlign::Left, "Press fire to return to the title screen");
         }
     }
 